@@ -0,0 +1,480 @@
+use std::any::Any;
+use std::collections::VecDeque;
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use Result;
+use kind::DataType;
+use session::{Input, Output, Session};
+use tensor::{AnyTensor, Tensor};
+use value::Value;
+
+/// A batcher.
+///
+/// The batcher accepts requests from many callers, joins the tensors of each
+/// input along the first dimension, runs the session once per batch, and
+/// splits the outputs back per request. The inputs can be of different data
+/// types. The data type and all the dimensions but the first one of each input
+/// are fixed by the first request, and mismatching requests are rejected.
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use tensorflux::{BatchOptions, Batcher, Buffer, Input, Options, Session, Tensor};
+///
+/// let mut session = Session::new(&Options::new().unwrap()).unwrap();
+/// session.extend(&Buffer::load("graph.pb").unwrap()).unwrap();
+/// let batcher = Batcher::new(Arc::new(session), &["ids:0", "x:0"], &["y:0"],
+///                            BatchOptions::default()).unwrap();
+///
+/// let ids = Tensor::new(vec![42i64], &[1]).unwrap();
+/// let x = Tensor::new(vec![1f32, 2.0, 3.0], &[1, 3]).unwrap();
+/// let mut outputs = batcher.run(vec![Input::new("ids:0", ids), Input::new("x:0", x)]).unwrap();
+/// let y = outputs.remove(0).downcast::<f32>().ok().unwrap();
+/// ```
+pub struct Batcher {
+    shared: Arc<Shared>,
+    worker: Option<JoinHandle<()>>,
+}
+
+/// Options of a batcher.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BatchOptions {
+    /// The maximal number of sub-tensors along the first dimension in a batch.
+    pub max_batch_size: usize,
+    /// The maximal time a request waits for a batch to fill up.
+    pub max_latency: Duration,
+    /// The flag padding each batch up to the maximal size by repeating the
+    /// last sub-tensor of each input.
+    pub padding: bool,
+}
+
+type Erased = (DataType, Box<Any + Send>);
+type Layout = Vec<(DataType, Vec<usize>)>;
+
+struct Shared {
+    session: Arc<Session>,
+    inputs: Vec<String>,
+    outputs: Vec<String>,
+    options: BatchOptions,
+    queue: Mutex<Queue>,
+    condition: Condvar,
+}
+
+struct Queue {
+    requests: VecDeque<Request>,
+    layout: Option<Layout>,
+    closed: bool,
+}
+
+struct Request {
+    tensors: Vec<Erased>,
+    rows: usize,
+    arrival: Instant,
+    sender: Sender<Result<Vec<AnyTensor>>>,
+}
+
+macro_rules! dispatch(
+    ($data_type:expr, $function:ident($($argument:expr),*)) => (match $data_type {
+        DataType::Bool => $function::<bool>($($argument),*),
+        DataType::Float => $function::<f32>($($argument),*),
+        DataType::Double => $function::<f64>($($argument),*),
+        DataType::Int8 => $function::<i8>($($argument),*),
+        DataType::Int16 => $function::<i16>($($argument),*),
+        DataType::Int32 => $function::<i32>($($argument),*),
+        DataType::Int64 => $function::<i64>($($argument),*),
+        DataType::UInt8 => $function::<u8>($($argument),*),
+        DataType::UInt16 => $function::<u16>($($argument),*),
+        DataType::String => $function::<Vec<u8>>($($argument),*),
+        DataType::QInt8 => $function::<::QInt8>($($argument),*),
+        DataType::QUInt8 => $function::<::QUInt8>($($argument),*),
+        DataType::QInt16 => $function::<::QInt16>($($argument),*),
+        DataType::QUInt16 => $function::<::QUInt16>($($argument),*),
+        DataType::QInt32 => $function::<::QInt32>($($argument),*),
+        #[cfg(feature = "complex")]
+        DataType::Complex64 => $function::<::c32>($($argument),*),
+        #[cfg(feature = "complex")]
+        DataType::Complex128 => $function::<::c64>($($argument),*),
+        #[cfg(feature = "half")]
+        DataType::BFloat16 => $function::<::bf16>($($argument),*),
+        #[cfg(feature = "half")]
+        DataType::Half => $function::<::f16>($($argument),*),
+        data_type => raise!("cannot batch tensors of data type {}", data_type),
+    });
+);
+
+impl Batcher {
+    /// Create a batcher.
+    ///
+    /// The inputs and outputs are given as names of tensors in the graph of
+    /// the session.
+    pub fn new<T, U>(session: Arc<Session>, inputs: &[T], outputs: &[U], options: BatchOptions)
+                     -> Result<Self> where T: AsRef<str>, U: AsRef<str> {
+
+        if options.max_batch_size == 0 {
+            raise!("the maximal batch size should be positive");
+        }
+        if inputs.is_empty() {
+            raise!("there should be at least one input");
+        }
+        let shared = Arc::new(Shared {
+            session: session,
+            inputs: inputs.iter().map(|name| name.as_ref().to_string()).collect(),
+            outputs: outputs.iter().map(|name| name.as_ref().to_string()).collect(),
+            options: options,
+            queue: Mutex::new(Queue { requests: VecDeque::new(), layout: None, closed: false }),
+            condition: Condvar::new(),
+        });
+        let worker = {
+            let shared = shared.clone();
+            thread::spawn(move || shared.work())
+        };
+        Ok(Batcher { shared: shared, worker: Some(worker) })
+    }
+
+    /// Run a request.
+    ///
+    /// The inputs should be named after the inputs of the batcher, own their
+    /// tensors, and have the same size of the first dimension. The call blocks
+    /// until the batch containing the request has been processed, and the
+    /// outputs are returned in the order of the outputs of the batcher.
+    pub fn run(&self, inputs: Vec<Input>) -> Result<Vec<AnyTensor>> {
+        let (tensors, rows, layout) = try!(self.shared.prepare(inputs));
+        let (sender, receiver) = mpsc::channel();
+        {
+            let mut queue = recover!(self.shared.queue.lock());
+            if let Some(ref expected) = queue.layout {
+                try!(self.shared.compare(expected, &layout));
+            }
+            if queue.layout.is_none() {
+                queue.layout = Some(layout);
+            }
+            queue.requests.push_back(Request {
+                tensors: tensors,
+                rows: rows,
+                arrival: Instant::now(),
+                sender: sender,
+            });
+        }
+        self.shared.condition.notify_one();
+        match receiver.recv() {
+            Ok(result) => result,
+            _ => raise!("the batcher has stopped"),
+        }
+    }
+}
+
+impl Drop for Batcher {
+    fn drop(&mut self) {
+        recover!(self.shared.queue.lock()).closed = true;
+        self.shared.condition.notify_one();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl Default for BatchOptions {
+    #[inline]
+    fn default() -> Self {
+        BatchOptions {
+            max_batch_size: 32,
+            max_latency: Duration::from_millis(10),
+            padding: false,
+        }
+    }
+}
+
+impl Shared {
+    fn prepare(&self, mut inputs: Vec<Input>) -> Result<(Vec<Erased>, usize, Layout)> {
+        if inputs.len() != self.inputs.len() {
+            raise!("there should be {} input(s), but {} given", self.inputs.len(), inputs.len());
+        }
+        let mut tensors = Vec::with_capacity(inputs.len());
+        let mut layout = Vec::with_capacity(inputs.len());
+        let mut rows = None;
+        for name in &self.inputs {
+            let mut input = match inputs.iter().position(|input| input.name() == *name) {
+                Some(i) => inputs.swap_remove(i),
+                _ => raise!("input `{}` has not been given", name),
+            };
+            let (data_type, dimensions) = match (input.data_type(), input.dimensions()) {
+                (Some(data_type), Some(dimensions)) => (data_type, dimensions),
+                _ => raise!("input `{}` has not been set", name),
+            };
+            let count = match dimensions.first() {
+                Some(&count) => count,
+                _ => raise!("cannot batch input `{}` of rank zero", name),
+            };
+            match rows {
+                Some(rows) if rows != count => {
+                    raise!("input `{}` should have {} sub-tensor(s), but it has dimensions {:?}",
+                           name, rows, dimensions);
+                },
+                _ => rows = Some(count),
+            }
+            let tensor = match data_type {
+                DataType::String => try!(take_strings(&mut input)),
+                _ => try!(dispatch!(data_type, take(&mut input))),
+            };
+            tensors.push((data_type, tensor));
+            layout.push((data_type, dimensions[1..].to_vec()));
+        }
+        let rows = rows.unwrap_or(0);
+        if rows == 0 || rows > self.options.max_batch_size {
+            raise!("the number of sub-tensors should be between 1 and {}, but {} given",
+                   self.options.max_batch_size, rows);
+        }
+        Ok((tensors, rows, layout))
+    }
+
+    fn compare(&self, expected: &[(DataType, Vec<usize>)], given: &[(DataType, Vec<usize>)])
+               -> Result<()> {
+
+        for ((name, expected), given) in self.inputs.iter().zip(expected).zip(given) {
+            if expected != given {
+                raise!("input `{}` should have data type {} and sub-tensors of dimensions {:?}, \
+                        but it has data type {} and sub-tensors of dimensions {:?}",
+                       name, expected.0, expected.1, given.0, given.1);
+            }
+        }
+        Ok(())
+    }
+
+    fn work(&self) {
+        while let Some(mut requests) = collect(&self.queue, &self.condition, &self.options) {
+            let rows = requests.iter().map(|request| request.rows).collect::<Vec<_>>();
+            let columns = transpose(&mut requests, self.inputs.len());
+            match self.process(columns, &rows) {
+                Ok(results) => for (request, result) in requests.iter().zip(results) {
+                    let _ = request.sender.send(Ok(result));
+                },
+                Err(error) => for request in &requests {
+                    let _ = request.sender.send(Err(error.clone()));
+                },
+            }
+        }
+    }
+
+    fn process(&self, columns: Vec<Vec<Erased>>, rows: &[usize])
+               -> Result<Vec<Vec<AnyTensor>>> {
+
+        let padding = if self.options.padding { Some(self.options.max_batch_size) } else { None };
+        let mut inputs = Vec::with_capacity(columns.len());
+        for (name, column) in self.inputs.iter().zip(columns) {
+            let data_type = column[0].0;
+            let tensors = column.into_iter().map(|(_, tensor)| tensor).collect::<Vec<_>>();
+            inputs.push(try!(dispatch!(data_type, join(name, tensors, padding))));
+        }
+        let mut outputs = self.outputs.iter().map(|name| Output::new(&name[..]))
+                                             .collect::<Vec<_>>();
+        try!(self.session.run(&inputs, &mut outputs, &[], None, None));
+        let mut results = rows.iter().map(|_| Vec::with_capacity(outputs.len()))
+                                     .collect::<Vec<_>>();
+        for (name, output) in self.outputs.iter().zip(&mut outputs) {
+            let tensor = try!(output.take_any());
+            let data_type = tensor.data_type();
+            let tensors = try!(dispatch!(data_type, divide(name, tensor, rows)));
+            for (result, tensor) in results.iter_mut().zip(tensors) {
+                result.push(tensor);
+            }
+        }
+        Ok(results)
+    }
+}
+
+fn collect(queue: &Mutex<Queue>, condition: &Condvar, options: &BatchOptions)
+           -> Option<Vec<Request>> {
+
+    let limit = options.max_batch_size;
+    let mut queue = recover!(queue.lock());
+    loop {
+        let deadline = match queue.requests.front() {
+            Some(request) => request.arrival + options.max_latency,
+            _ if queue.closed => return None,
+            _ => {
+                queue = recover!(condition.wait(queue));
+                continue;
+            },
+        };
+        let now = Instant::now();
+        let rows = queue.requests.iter().fold(0, |sum, request| sum + request.rows);
+        if queue.closed || rows >= limit || now >= deadline {
+            break;
+        }
+        queue = recover!(condition.wait_timeout(queue, deadline - now)).0;
+    }
+    let mut requests = Vec::new();
+    let mut rows = 0;
+    while let Some(request) = queue.requests.pop_front() {
+        if !requests.is_empty() && rows + request.rows > limit {
+            queue.requests.push_front(request);
+            break;
+        }
+        rows += request.rows;
+        requests.push(request);
+    }
+    Some(requests)
+}
+
+fn transpose(requests: &mut [Request], count: usize)
+             -> Vec<Vec<Erased>> {
+
+    let mut columns = (0..count).map(|_| Vec::with_capacity(requests.len()))
+                                .collect::<Vec<_>>();
+    for request in requests {
+        for (column, tensor) in columns.iter_mut().zip(request.tensors.drain(..)) {
+            column.push(tensor);
+        }
+    }
+    columns
+}
+
+fn take<T>(input: &mut Input) -> Result<Box<Any + Send>> where T: Value {
+    Ok(Box::new(try!(input.get::<T>())))
+}
+
+fn take_strings(input: &mut Input) -> Result<Box<Any + Send>> {
+    if let Ok(tensor) = input.get::<Vec<u8>>() {
+        return Ok(Box::new(tensor));
+    }
+    let tensor = try!(input.get::<String>());
+    let dimensions = tensor.dimensions();
+    let data: Vec<String> = tensor.into();
+    let data = data.into_iter().map(String::into_bytes).collect();
+    Ok(Box::new(try!(Tensor::<Vec<u8>>::new(data, &dimensions))))
+}
+
+fn join<T>(name: &str, tensors: Vec<Box<Any + Send>>, padding: Option<usize>)
+           -> Result<Input<'static>> where T: Value {
+
+    let mut typed = Vec::with_capacity(tensors.len());
+    for tensor in tensors {
+        match tensor.downcast::<Tensor<T>>() {
+            Ok(tensor) => typed.push(*tensor),
+            _ => raise!("input `{}` has tensors of different types", name),
+        }
+    }
+    if let Some(size) = padding {
+        try!(pad(&mut typed, size));
+    }
+    Ok(Input::new(name, try!(Tensor::concatenate(&typed))))
+}
+
+fn divide<T>(name: &str, tensor: AnyTensor, rows: &[usize]) -> Result<Vec<AnyTensor>>
+    where T: Value
+{
+    let tensor = match tensor.downcast::<T>() {
+        Ok(tensor) => tensor,
+        _ => raise!("output `{}` cannot be decoded", name),
+    };
+    let mut tensors = Vec::with_capacity(rows.len());
+    for tensor in try!(split(tensor, rows)) {
+        tensors.push(AnyTensor::from_raw(try!(tensor.to_raw())));
+    }
+    Ok(tensors)
+}
+
+fn pad<T>(tensors: &mut Vec<Tensor<T>>, size: usize) -> Result<()> where T: Value {
+    let rows = tensors.iter().fold(0, |sum, tensor| sum + tensor.dimensions()[0]);
+    if rows >= size {
+        return Ok(());
+    }
+    let last = {
+        let tensor = &tensors[tensors.len() - 1];
+        let count = tensor.dimensions()[0];
+        try!(tensor.slice((count - 1)..count))
+    };
+    for _ in (rows + 1)..size {
+        tensors.push(try!(last.slice(0..1)));
+    }
+    tensors.push(last);
+    Ok(())
+}
+
+fn split<T>(tensor: Tensor<T>, rows: &[usize]) -> Result<Vec<Tensor<T>>> where T: Value {
+    let mut tensors = Vec::with_capacity(rows.len());
+    let mut start = 0;
+    for &count in rows {
+        tensors.push(try!(tensor.slice(start..(start + count))));
+        start += count;
+    }
+    Ok(tensors)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+    use std::sync::mpsc;
+    use std::sync::{Condvar, Mutex};
+    use std::time::{Duration, Instant};
+
+    use batcher::{BatchOptions, Queue, Request, collect, pad, split, take, take_strings};
+    use session::Input;
+    use tensor::Tensor;
+
+    #[test]
+    fn collect_requests() {
+        let options = BatchOptions { max_batch_size: 4, max_latency: Duration::from_millis(50),
+                                     padding: false };
+        let queue = Mutex::new(Queue { requests: VecDeque::new(), layout: None, closed: false });
+        let condition = Condvar::new();
+        let (sender, _receiver) = mpsc::channel();
+        let push = |rows| {
+            let request = Request { tensors: vec![], rows: rows, arrival: Instant::now(),
+                                    sender: sender.clone() };
+            queue.lock().unwrap().requests.push_back(request);
+        };
+        let sizes = |requests: Vec<Request>| {
+            requests.iter().map(|request| request.rows).collect::<Vec<_>>()
+        };
+
+        push(1);
+        push(2);
+        push(3);
+        let start = Instant::now();
+        assert_eq!(sizes(collect(&queue, &condition, &options).unwrap()), vec![1, 2]);
+        assert!(start.elapsed() < options.max_latency);
+
+        let start = Instant::now();
+        assert_eq!(sizes(collect(&queue, &condition, &options).unwrap()), vec![3]);
+        assert!(start.elapsed() >= options.max_latency);
+
+        push(1);
+        queue.lock().unwrap().closed = true;
+        let start = Instant::now();
+        assert_eq!(sizes(collect(&queue, &condition, &options).unwrap()), vec![1]);
+        assert!(start.elapsed() < options.max_latency);
+        assert!(collect(&queue, &condition, &options).is_none());
+    }
+
+    #[test]
+    fn pad_split() {
+        let mut tensors = vec![
+            Tensor::new(vec![1, 2], &[1, 2]).unwrap(),
+            Tensor::new(vec![3, 4, 5, 6], &[2, 2]).unwrap(),
+        ];
+        pad(&mut tensors, 5).unwrap();
+        let tensor = Tensor::concatenate(&tensors).unwrap();
+        assert_eq!(tensor.dimensions(), vec![5, 2]);
+        assert_eq!(&tensor[..], &[1, 2, 3, 4, 5, 6, 5, 6, 5, 6]);
+
+        let tensors = split(tensor, &[1, 2]).unwrap();
+        assert_eq!(&tensors[0][..], &[1, 2]);
+        assert_eq!(&tensors[1][..], &[3, 4, 5, 6]);
+        assert!(split(Tensor::new(vec![1], &[1, 1]).unwrap(), &[2]).is_err());
+    }
+
+    #[test]
+    fn take_mixed() {
+        let mut input = Input::new("a", Tensor::new(vec![42i64], &[1]).unwrap());
+        assert!(take::<f32>(&mut input).is_err());
+        let tensor = take::<i64>(&mut input).unwrap();
+        assert_eq!(&tensor.downcast::<Tensor<i64>>().ok().unwrap()[..], &[42]);
+
+        let mut input = Input::new("b", Tensor::new(vec![String::from("foo")], &[1]).unwrap());
+        let tensor = take_strings(&mut input).unwrap();
+        assert_eq!(&tensor.downcast::<Tensor<Vec<u8>>>().ok().unwrap()[..], &[b"foo".to_vec()]);
+    }
+}
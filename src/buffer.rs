@@ -1,11 +1,14 @@
 use ffi::TF_Buffer;
 use libc::size_t;
 use std::fs::File;
-use std::io::Read;
+use std::io::{Read, Write};
+use std::ops::{Deref, DerefMut};
 use std::path::Path;
+use std::ptr;
 
 use Result;
 use memory::Memory;
+use protobuf::Message;
 
 /// A buffer.
 pub struct Buffer {
@@ -21,6 +24,8 @@ impl Buffer {
     }
 
     /// Create a buffer from raw parts.
+    ///
+    /// The data are not freed when the buffer is dropped.
     #[inline]
     pub unsafe fn from_raw_parts(pointer: *mut u8, length: usize) -> Buffer {
         Memory::from_raw_parts(pointer, length).into()
@@ -34,28 +39,94 @@ impl Buffer {
         Ok(Buffer::new(data))
     }
 
+    /// Write the buffer.
+    pub fn write<T>(&self, path: T) -> Result<()> where T: AsRef<Path> {
+        let mut file = ok!(File::create(path));
+        ok!(file.write_all(self));
+        Ok(())
+    }
+
     #[doc(hidden)]
     #[inline]
     pub fn as_raw(&self) -> *mut TF_Buffer {
         &self.raw as *const _ as *mut _
     }
 
+    /// Take the ownership of the data written by TensorFlow.
     #[doc(hidden)]
     pub unsafe fn reset(&mut self) {
-        self.memory = Memory::from_raw_parts(self.raw.data as *mut _, self.raw.length as usize);
+        self.memory = Memory::from_buffer(self.raw.data as *mut _, self.raw.length as usize,
+                                          self.raw.deallocator.take());
+    }
+}
+
+unsafe impl Send for Buffer {}
+
+impl Deref for Buffer {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &[u8] {
+        &self.memory
+    }
+}
+
+impl DerefMut for Buffer {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        let data = &mut *self.memory;
+        self.raw.data = pointer(data) as *mut _;
+        data
+    }
+}
+
+impl AsRef<[u8]> for Buffer {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        &self.memory
     }
 }
 
-memory!(Buffer<u8>);
+impl Into<Vec<u8>> for Buffer {
+    #[inline]
+    fn into(self) -> Vec<u8> {
+        self.memory.into()
+    }
+}
+
+impl<'l, T> From<&'l T> for Buffer where T: Message {
+    #[inline]
+    fn from(message: &'l T) -> Self {
+        Buffer::new(message.encode())
+    }
+}
 
 #[doc(hidden)]
 impl From<Memory<u8>> for Buffer {
     fn from(memory: Memory<u8>) -> Self {
         let raw = TF_Buffer {
-            data: memory.as_ptr() as *mut _,
+            data: pointer(&memory) as *mut _,
             length: memory.len() as size_t,
             deallocator: None,
         };
         Buffer { memory: memory, raw: raw }
     }
 }
+
+#[inline]
+fn pointer(data: &[u8]) -> *const u8 {
+    if data.is_empty() { ptr::null() } else { data.as_ptr() }
+}
+
+#[cfg(test)]
+mod tests {
+    use buffer::Buffer;
+    use std::{env, fs};
+
+    #[test]
+    fn write_load() {
+        let path = env::temp_dir().join("tensorflux-buffer-write-load.pb");
+        Buffer::new(vec![1, 2, 3]).write(&path).unwrap();
+        assert_eq!(&Buffer::load(&path).unwrap()[..], &[1, 2, 3]);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,250 @@
+use std::collections::BTreeMap;
+
+use Result;
+use protobuf::{Codec, Reader, Wire, Writer};
+
+/// A configuration.
+///
+/// The configuration corresponds to the `ConfigProto` protocol buffer, and it
+/// can be applied to options as follows:
+///
+/// ```
+/// use tensorflux::{Buffer, Config, Options};
+///
+/// let mut config = Config::default();
+/// config.intra_op_parallelism_threads = 4;
+/// config.inter_op_parallelism_threads = 2;
+/// config.allow_soft_placement = true;
+/// config.device_count.insert("GPU".into(), 0);
+///
+/// let mut options = Options::new().unwrap();
+/// options.configure(&Buffer::from(&config)).unwrap();
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Config {
+    /// The maximal number of devices of each type to use.
+    pub device_count: BTreeMap<String, i32>,
+    /// The number of threads for parallelizing individual operations.
+    pub intra_op_parallelism_threads: i32,
+    /// The number of threads for executing independent operations.
+    pub inter_op_parallelism_threads: i32,
+    /// The flag indicating that the session should have its own thread pools.
+    pub use_per_session_threads: bool,
+    /// The period in steps between re-evaluations of the placement.
+    pub placement_period: i32,
+    /// The devices to consider, given as filters.
+    pub device_filters: Vec<String>,
+    /// The flag allowing operations to be placed on devices other than the
+    /// requested ones.
+    pub allow_soft_placement: bool,
+    /// The flag enabling the logging of the placement of operations.
+    pub log_device_placement: bool,
+    /// The graph options.
+    pub graph_options: Option<GraphOptions>,
+    /// The timeout of blocking operations in milliseconds.
+    pub operation_timeout_in_ms: i64,
+}
+
+/// Graph options.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GraphOptions {
+    /// The flag enabling the scheduling of receive operations.
+    pub enable_recv_scheduling: bool,
+    /// The optimizer options.
+    pub optimizer_options: Option<OptimizerOptions>,
+    /// The number of steps after which to build a cost model.
+    pub build_cost_model: i64,
+    /// The number of steps to run before building a cost model.
+    pub build_cost_model_after: i64,
+    /// The flag annotating nodes with output shapes.
+    pub infer_shapes: bool,
+    /// The flag placing only the subgraph that is run.
+    pub place_pruned_graph: bool,
+    /// The flag enabling the conversion to `bfloat16` when sending data.
+    pub enable_bfloat16_sendrecv: bool,
+    /// The step at which to record a timeline.
+    pub timeline_step: i32,
+}
+
+/// Optimizer options.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OptimizerOptions {
+    /// The flag enabling the elimination of common subexpressions.
+    pub do_common_subexpression_elimination: bool,
+    /// The flag enabling constant folding.
+    pub do_constant_folding: bool,
+    /// The flag enabling the inlining of functions.
+    pub do_function_inlining: bool,
+    /// The level of optimization.
+    pub opt_level: OptimizerLevel,
+    /// The level of just-in-time compilation.
+    pub global_jit_level: JitLevel,
+}
+
+/// A level of optimization.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OptimizerLevel {
+    /// Perform common-subexpression elimination and constant folding.
+    L1,
+    /// Perform no optimizations.
+    L0,
+}
+
+/// A level of just-in-time compilation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JitLevel {
+    /// Let TensorFlow decide.
+    Default,
+    /// Disable the compilation.
+    Off,
+    /// Enable the compilation at the first level.
+    On1,
+    /// Enable the compilation at the second level.
+    On2,
+}
+
+impl Default for OptimizerLevel {
+    #[inline]
+    fn default() -> Self {
+        OptimizerLevel::L1
+    }
+}
+
+impl Default for JitLevel {
+    #[inline]
+    fn default() -> Self {
+        JitLevel::Default
+    }
+}
+
+impl Codec for Config {
+    fn read(&mut self, field: u32, wire: Wire, reader: &mut Reader) -> Result<()> {
+        match field {
+            1 => try!(reader.entry(wire, &mut self.device_count, Reader::int32)),
+            2 => self.intra_op_parallelism_threads = try!(reader.int32(wire)),
+            3 => self.placement_period = try!(reader.int32(wire)),
+            4 => self.device_filters.push(try!(reader.string(wire))),
+            5 => self.inter_op_parallelism_threads = try!(reader.int32(wire)),
+            7 => self.allow_soft_placement = try!(reader.bool(wire)),
+            8 => self.log_device_placement = try!(reader.bool(wire)),
+            9 => self.use_per_session_threads = try!(reader.bool(wire)),
+            10 => self.graph_options = Some(try!(reader.message(wire))),
+            11 => self.operation_timeout_in_ms = try!(reader.int64(wire)),
+            _ => try!(reader.skip(wire)),
+        }
+        Ok(())
+    }
+
+    fn write(&self, writer: &mut Writer) {
+        for (key, &value) in &self.device_count {
+            writer.entry(1, key, |writer| writer.int32(2, value));
+        }
+        writer.int32(2, self.intra_op_parallelism_threads);
+        writer.int32(3, self.placement_period);
+        for filter in &self.device_filters {
+            writer.delimited(4, filter.as_bytes());
+        }
+        writer.int32(5, self.inter_op_parallelism_threads);
+        writer.bool(7, self.allow_soft_placement);
+        writer.bool(8, self.log_device_placement);
+        writer.bool(9, self.use_per_session_threads);
+        if let Some(ref options) = self.graph_options {
+            writer.message(10, options);
+        }
+        writer.int64(11, self.operation_timeout_in_ms);
+    }
+}
+
+impl Codec for GraphOptions {
+    fn read(&mut self, field: u32, wire: Wire, reader: &mut Reader) -> Result<()> {
+        match field {
+            2 => self.enable_recv_scheduling = try!(reader.bool(wire)),
+            3 => self.optimizer_options = Some(try!(reader.message(wire))),
+            4 => self.build_cost_model = try!(reader.int64(wire)),
+            5 => self.infer_shapes = try!(reader.bool(wire)),
+            6 => self.place_pruned_graph = try!(reader.bool(wire)),
+            7 => self.enable_bfloat16_sendrecv = try!(reader.bool(wire)),
+            8 => self.timeline_step = try!(reader.int32(wire)),
+            9 => self.build_cost_model_after = try!(reader.int64(wire)),
+            _ => try!(reader.skip(wire)),
+        }
+        Ok(())
+    }
+
+    fn write(&self, writer: &mut Writer) {
+        writer.bool(2, self.enable_recv_scheduling);
+        if let Some(ref options) = self.optimizer_options {
+            writer.message(3, options);
+        }
+        writer.int64(4, self.build_cost_model);
+        writer.bool(5, self.infer_shapes);
+        writer.bool(6, self.place_pruned_graph);
+        writer.bool(7, self.enable_bfloat16_sendrecv);
+        writer.int32(8, self.timeline_step);
+        writer.int64(9, self.build_cost_model_after);
+    }
+}
+
+impl Codec for OptimizerOptions {
+    fn read(&mut self, field: u32, wire: Wire, reader: &mut Reader) -> Result<()> {
+        match field {
+            1 => self.do_common_subexpression_elimination = try!(reader.bool(wire)),
+            2 => self.do_constant_folding = try!(reader.bool(wire)),
+            3 => self.opt_level = match try!(reader.int32(wire)) {
+                -1 => OptimizerLevel::L0,
+                _ => OptimizerLevel::L1,
+            },
+            4 => self.do_function_inlining = try!(reader.bool(wire)),
+            5 => self.global_jit_level = match try!(reader.int32(wire)) {
+                -1 => JitLevel::Off,
+                1 => JitLevel::On1,
+                2 => JitLevel::On2,
+                _ => JitLevel::Default,
+            },
+            _ => try!(reader.skip(wire)),
+        }
+        Ok(())
+    }
+
+    fn write(&self, writer: &mut Writer) {
+        writer.bool(1, self.do_common_subexpression_elimination);
+        writer.bool(2, self.do_constant_folding);
+        writer.int32(3, match self.opt_level {
+            OptimizerLevel::L1 => 0,
+            OptimizerLevel::L0 => -1,
+        });
+        writer.bool(4, self.do_function_inlining);
+        writer.int32(5, match self.global_jit_level {
+            JitLevel::Default => 0,
+            JitLevel::Off => -1,
+            JitLevel::On1 => 1,
+            JitLevel::On2 => 2,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use config::{Config, GraphOptions, OptimizerLevel, OptimizerOptions};
+    use protobuf::Message;
+
+    #[test]
+    fn encode() {
+        let mut config = Config::default();
+        config.intra_op_parallelism_threads = 4;
+        config.inter_op_parallelism_threads = 2;
+        config.device_count.insert("GPU".into(), 0);
+        assert_eq!(config.encode(), vec![
+            1 << 3 | 2, 5, 1 << 3 | 2, 3, b'G', b'P', b'U',
+            2 << 3, 4,
+            5 << 3, 2,
+        ]);
+
+        let mut optimizer = OptimizerOptions::default();
+        optimizer.opt_level = OptimizerLevel::L0;
+        let mut graph = GraphOptions::default();
+        graph.optimizer_options = Some(optimizer);
+        config.graph_options = Some(graph);
+        assert_eq!(Config::decode(&config.encode()).unwrap(), config);
+    }
+}
@@ -1,30 +1,138 @@
 use ffi::TF_Code;
 use std::ffi::CStr;
-use std::{error, fmt};
+use std::sync::Arc;
+use std::{error, fmt, io};
 
 use status::Status;
 
 /// An error.
 #[derive(Clone, Debug)]
 pub struct Error {
+    kind: ErrorKind,
     message: String,
+    source: Option<Arc<io::Error>>,
+}
+
+/// A kind of error.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// An error detected on the Rust side prior to calling TensorFlow.
+    Validation,
+    /// An input–output error.
+    Io,
+    /// An error reported by TensorFlow.
+    TensorFlow(Code),
+}
+
+/// A status code of TensorFlow.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Code {
+    /// The operation was cancelled.
+    Cancelled,
+    /// The error is unknown.
+    Unknown,
+    /// An argument is invalid.
+    InvalidArgument,
+    /// The deadline expired before the operation could complete.
+    DeadlineExceeded,
+    /// An entity was not found.
+    NotFound,
+    /// An entity already exists.
+    AlreadyExists,
+    /// The caller does not have permission.
+    PermissionDenied,
+    /// The caller is not authenticated.
+    Unauthenticated,
+    /// A resource has been exhausted.
+    ResourceExhausted,
+    /// The system is not in a state required for the operation.
+    FailedPrecondition,
+    /// The operation was aborted.
+    Aborted,
+    /// The operation was attempted past the valid range.
+    OutOfRange,
+    /// The operation is not implemented or supported.
+    Unimplemented,
+    /// An internal invariant is broken.
+    Internal,
+    /// The service is currently unavailable.
+    Unavailable,
+    /// The data are lost or corrupted.
+    DataLoss,
 }
 
 impl Error {
+    /// Return the kind.
+    #[inline]
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// Return the status code if the error was reported by TensorFlow.
+    #[inline]
+    pub fn code(&self) -> Option<Code> {
+        match self.kind {
+            ErrorKind::TensorFlow(code) => Some(code),
+            _ => None,
+        }
+    }
+
+    /// Return the message.
+    #[inline]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
     #[doc(hidden)]
     pub fn from_status(status: &Status) -> Option<Self> {
-        if ffi!(TF_GetCode(status.as_raw())) == TF_Code::TF_OK {
-            return None;
-        }
+        let code = match ffi!(TF_GetCode(status.as_raw())) {
+            TF_Code::TF_OK => return None,
+            TF_Code::TF_CANCELLED => Code::Cancelled,
+            TF_Code::TF_UNKNOWN => Code::Unknown,
+            TF_Code::TF_INVALID_ARGUMENT => Code::InvalidArgument,
+            TF_Code::TF_DEADLINE_EXCEEDED => Code::DeadlineExceeded,
+            TF_Code::TF_NOT_FOUND => Code::NotFound,
+            TF_Code::TF_ALREADY_EXISTS => Code::AlreadyExists,
+            TF_Code::TF_PERMISSION_DENIED => Code::PermissionDenied,
+            TF_Code::TF_UNAUTHENTICATED => Code::Unauthenticated,
+            TF_Code::TF_RESOURCE_EXHAUSTED => Code::ResourceExhausted,
+            TF_Code::TF_FAILED_PRECONDITION => Code::FailedPrecondition,
+            TF_Code::TF_ABORTED => Code::Aborted,
+            TF_Code::TF_OUT_OF_RANGE => Code::OutOfRange,
+            TF_Code::TF_UNIMPLEMENTED => Code::Unimplemented,
+            TF_Code::TF_INTERNAL => Code::Internal,
+            TF_Code::TF_UNAVAILABLE => Code::Unavailable,
+            TF_Code::TF_DATA_LOSS => Code::DataLoss,
+        };
         let message = ffi!(TF_Message(status.as_raw()));
-        if message.is_null() {
-            return None;
-        }
-        let message = match unsafe { CStr::from_ptr(message).to_str() } {
-            Ok(message) => message.into(),
-            _ => String::new(),
+        let message = if message.is_null() { String::new() } else {
+            match unsafe { CStr::from_ptr(message).to_str() } {
+                Ok(message) => message.into(),
+                _ => String::new(),
+            }
         };
-        Some(Error { message: message })
+        Some(Error { kind: ErrorKind::TensorFlow(code), message: message, source: None })
+    }
+
+    /// Prefix the message with a description of where the error occurred.
+    #[doc(hidden)]
+    pub fn within<T>(mut self, context: T) -> Self where T: AsRef<str> {
+        self.message = format!("{}: {}", context.as_ref(), self.message);
+        self
+    }
+
+    #[doc(hidden)]
+    pub fn from_io(error: io::Error) -> Self {
+        Error { kind: ErrorKind::Io, message: error.to_string(), source: Some(Arc::new(error)) }
+    }
+
+    #[doc(hidden)]
+    pub fn from_null() -> Self {
+        Error {
+            kind: ErrorKind::TensorFlow(Code::Unknown),
+            message: "failed to call TensorFlow".into(),
+            source: None,
+        }
     }
 }
 
@@ -40,11 +148,42 @@ impl error::Error for Error {
     fn description(&self) -> &str {
         &self.message
     }
+
+    fn source(&self) -> Option<&(error::Error + 'static)> {
+        match self.source {
+            Some(ref error) => Some(&**error),
+            _ => None,
+        }
+    }
 }
 
 impl<T> From<T> for Error where T: Into<String> {
     #[inline]
     fn from(message: T) -> Error {
-        Error { message: message.into() }
+        Error { kind: ErrorKind::Validation, message: message.into(), source: None }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use error::{Error, ErrorKind};
+    use std::error::Error as Trait;
+    use std::io;
+
+    #[test]
+    fn source() {
+        let error = Error::from("the data types do not match");
+        assert_eq!(error.kind(), ErrorKind::Validation);
+        assert!(error.code().is_none());
+        assert!(error.source().is_none());
+
+        let error = Error::from_io(io::Error::new(io::ErrorKind::NotFound, "no such file"));
+        assert_eq!(error.kind(), ErrorKind::Io);
+        assert_eq!(error.message(), "no such file");
+        assert_eq!(error.source().unwrap().to_string(), "no such file");
+
+        let error = error.within("input `a`");
+        assert_eq!(error.kind(), ErrorKind::Io);
+        assert_eq!(error.to_string(), "input `a`: no such file");
     }
 }
@@ -0,0 +1,181 @@
+use std::any::Any;
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+
+use {Error, Result};
+
+/// A computation executed on a pool of worker threads.
+///
+/// The task is a future resolving to the result of the computation, which is
+/// an error if the computation fails or panics. Dropping the task cancels the
+/// computation if it has not started yet and discards its result otherwise.
+pub struct Task<T> {
+    state: Arc<Mutex<State<T>>>,
+}
+
+struct State<T> {
+    result: Option<Result<T>>,
+    waker: Option<Waker>,
+    cancelled: bool,
+}
+
+struct Pool {
+    sender: Mutex<Sender<Job>>,
+}
+
+type Job = Box<FnOnce() + Send>;
+
+/// Execute a computation on the pool of worker threads.
+///
+/// The number of threads is bounded by the available parallelism. A panic in
+/// the computation is caught and turned into an error.
+pub fn spawn<T, F>(compute: F) -> Task<T>
+    where T: Send + 'static, F: FnOnce() -> Result<T> + Send + 'static
+{
+    let state = Arc::new(Mutex::new(State { result: None, waker: None, cancelled: false }));
+    let shared = state.clone();
+    pool().execute(Box::new(move || {
+        if recover!(shared.lock()).cancelled {
+            return;
+        }
+        let result = match panic::catch_unwind(AssertUnwindSafe(compute)) {
+            Ok(result) => result,
+            Err(payload) => Err(Error::from(format!("the computation panicked: {}",
+                                                    describe(&*payload)))),
+        };
+        let mut state = recover!(shared.lock());
+        if state.cancelled {
+            return;
+        }
+        state.result = Some(result);
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    }));
+    Task { state: state }
+}
+
+impl<T> Future for Task<T> {
+    type Output = Result<T>;
+
+    fn poll(self: Pin<&mut Self>, context: &mut Context) -> Poll<Result<T>> {
+        let mut state = recover!(self.state.lock());
+        match state.result.take() {
+            Some(result) => Poll::Ready(result),
+            _ => {
+                state.waker = Some(context.waker().clone());
+                Poll::Pending
+            },
+        }
+    }
+}
+
+impl<T> Drop for Task<T> {
+    #[inline]
+    fn drop(&mut self) {
+        let mut state = recover!(self.state.lock());
+        state.cancelled = true;
+        state.result = None;
+    }
+}
+
+impl Pool {
+    fn new(threads: usize) -> Self {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        for _ in 0..threads {
+            let receiver = receiver.clone();
+            thread::spawn(move || work(&receiver));
+        }
+        Pool { sender: Mutex::new(sender) }
+    }
+
+    fn execute(&self, job: Job) {
+        let _ = recover!(self.sender.lock()).send(job);
+    }
+}
+
+fn pool() -> &'static Pool {
+    static POOL: OnceLock<Pool> = OnceLock::new();
+    POOL.get_or_init(|| {
+        Pool::new(thread::available_parallelism().map(|count| count.get()).unwrap_or(1))
+    })
+}
+
+fn describe(payload: &(Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "unknown cause"
+    }
+}
+
+fn work(receiver: &Mutex<Receiver<Job>>) {
+    loop {
+        let job = match recover!(receiver.lock()).recv() {
+            Ok(job) => job,
+            _ => return,
+        };
+        job();
+    }
+}
+
+#[cfg(test)]
+struct Unpark(thread::Thread);
+
+#[cfg(test)]
+impl ::std::task::Wake for Unpark {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+#[cfg(test)]
+pub fn block_on<T: Future>(mut future: T) -> T::Output {
+    let waker = Arc::new(Unpark(thread::current())).into();
+    let mut context = Context::from_waker(&waker);
+    let mut future = unsafe { Pin::new_unchecked(&mut future) };
+    loop {
+        match future.as_mut().poll(&mut context) {
+            Poll::Ready(result) => return result,
+            _ => thread::park(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use executor::{block_on, spawn};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn spawn_many() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let tasks = (0..32).map(|i| {
+            let counter = counter.clone();
+            spawn(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+                Ok(i * i)
+            })
+        }).collect::<Vec<_>>();
+        let results = tasks.into_iter().map(|task| block_on(task).unwrap()).collect::<Vec<_>>();
+        assert_eq!(results, (0..32).map(|i| i * i).collect::<Vec<_>>());
+        assert_eq!(counter.load(Ordering::SeqCst), 32);
+    }
+
+    #[test]
+    fn spawn_panicking() {
+        let task = spawn(|| -> ::Result<usize> { panic!("out of order") });
+        let error = block_on(task).err().unwrap();
+        assert_eq!(error.to_string(), "the computation panicked: out of order");
+        assert_eq!(block_on(spawn(|| Ok(42))).unwrap(), 42);
+    }
+}
@@ -0,0 +1,873 @@
+//! Graphs and graph definitions.
+//!
+//! A graph can be constructed directly using `Graph`. The rest of the types in
+//! this module mirror the protocol buffers that TensorFlow uses to describe
+//! graphs, and they can be found in TensorFlow’s [repository][1]. A definition
+//! can be decoded from a buffer, inspected, modified, and encoded back:
+//!
+//! ```
+//! use tensorflux::Buffer;
+//! use tensorflux::Message;
+//! use tensorflux::graph::GraphDef;
+//!
+//! # let path = "examples/assets/multiplication.pb";
+//! let buffer = Buffer::load(path).unwrap();
+//! let definition = GraphDef::from_buffer(&buffer).unwrap();
+//! for node in definition.placeholders() {
+//!     println!("{}", node.name);
+//! }
+//! let buffer = Buffer::from(&definition);
+//! # assert!(!buffer.is_empty());
+//! ```
+//!
+//! [1]: https://github.com/tensorflow/tensorflow/tree/master/tensorflow/core/framework
+
+use ffi::{TF_Graph, TF_ImportGraphDefOptions, TF_Operation, TF_OperationDescription, TF_Port};
+use libc::{c_char, c_int, size_t};
+use std::collections::{BTreeMap, BTreeSet};
+use std::ffi::CStr;
+use std::marker::PhantomData;
+
+use Result;
+use buffer::Buffer;
+use kind::DataType;
+use protobuf::{Codec, Message, Reader, Wire, Writer};
+use status::Status;
+use string::{cstring, split};
+use tensor::Tensor;
+use value::Value;
+
+/// A graph.
+///
+/// A graph can be constructed operation by operation and then run in a
+/// session:
+///
+/// ```
+/// use tensorflux::graph::Graph;
+/// use tensorflux::{DataType, Input, Options, Output, Session, Tensor};
+///
+/// macro_rules! ok(($result:expr) => ($result.unwrap()));
+///
+/// let graph = ok!(Graph::new());
+/// {
+///     let a = ok!(graph.placeholder("a", DataType::Float));
+///     let b = ok!(graph.placeholder("b", DataType::Float));
+///     ok!(graph.mul("c", a, b));
+/// }
+/// let session = ok!(Session::from_graph(graph, &ok!(Options::new())));
+///
+/// let a = ok!(Tensor::new(vec![1f32, 2.0, 3.0], &[3]));
+/// let b = ok!(Tensor::new(vec![4f32, 5.0, 6.0], &[3]));
+///
+/// let inputs = vec![Input::new("a", a), Input::new("b", b)];
+/// let mut outputs = vec![Output::new("c")];
+/// ok!(session.run(&inputs, &mut outputs, &[], None, None));
+///
+/// let c = ok!(outputs[0].get::<f32>());
+/// assert_eq!(&c[..], &[1.0 * 4.0, 2.0 * 5.0, 3.0 * 6.0]);
+/// ```
+pub struct Graph {
+    raw: *mut TF_Graph,
+}
+
+/// An operation of a graph.
+#[derive(Clone, Copy)]
+pub struct Operation<'l> {
+    raw: *mut TF_Operation,
+    phantom: PhantomData<&'l Graph>,
+}
+
+/// A builder of an operation.
+///
+/// The operation is added to the graph only by `finish`. The C API provides
+/// no means of discarding an unfinished operation, and, therefore, the memory
+/// of a builder dropped without calling `finish` is leaked.
+pub struct OperationBuilder<'l> {
+    status: Status,
+    raw: *mut TF_OperationDescription,
+    phantom: PhantomData<&'l Graph>,
+}
+
+/// Options for importing a graph definition.
+#[derive(Clone, Debug, Default)]
+pub struct ImportOptions {
+    /// The prefix prepended to the names of the imported nodes.
+    pub prefix: String,
+}
+
+struct RawImportOptions(*mut TF_ImportGraphDefOptions);
+
+/// An output of an operation.
+#[derive(Clone, Copy)]
+pub struct Port<'l> {
+    /// The operation.
+    pub operation: Operation<'l>,
+    /// The index of the output.
+    pub index: usize,
+}
+
+/// A graph definition.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GraphDef {
+    /// The nodes.
+    pub node: Vec<NodeDef>,
+    /// The versions.
+    pub versions: Option<VersionDef>,
+    /// The function library as a serialized `FunctionDefLibrary`.
+    pub library: Vec<u8>,
+}
+
+/// A version definition.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VersionDef {
+    /// The version of the producer.
+    pub producer: i32,
+    /// The minimal version of the consumer.
+    pub min_consumer: i32,
+    /// The versions of the consumer that are not allowed.
+    pub bad_consumers: Vec<i32>,
+}
+
+/// A node definition.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NodeDef {
+    /// The name.
+    pub name: String,
+    /// The operation.
+    pub op: String,
+    /// The inputs in the form `node`, `node:index`, or `^node`.
+    pub input: Vec<String>,
+    /// The device.
+    pub device: String,
+    /// The attributes.
+    pub attr: BTreeMap<String, AttrValue>,
+}
+
+/// An attribute value.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AttrValue {
+    /// No value.
+    Empty,
+    /// A list of values.
+    List(AttrList),
+    /// A byte string.
+    String(Vec<u8>),
+    /// An integer.
+    Int(i64),
+    /// A floating-point number.
+    Float(f32),
+    /// A Boolean value.
+    Bool(bool),
+    /// A data type.
+    Type(DataType),
+    /// A shape.
+    Shape(TensorShapeProto),
+    /// A tensor.
+    Tensor(TensorProto),
+    /// A placeholder to be substituted in a function.
+    Placeholder(String),
+    /// A function.
+    Function(NameAttrList),
+}
+
+/// A list of attribute values.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AttrList {
+    /// The byte strings.
+    pub s: Vec<Vec<u8>>,
+    /// The integers.
+    pub i: Vec<i64>,
+    /// The floating-point numbers.
+    pub f: Vec<f32>,
+    /// The Boolean values.
+    pub b: Vec<bool>,
+    /// The data types.
+    pub type_: Vec<DataType>,
+    /// The shapes.
+    pub shape: Vec<TensorShapeProto>,
+    /// The tensors.
+    pub tensor: Vec<TensorProto>,
+    /// The functions.
+    pub func: Vec<NameAttrList>,
+}
+
+/// A name with attributes.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NameAttrList {
+    /// The name.
+    pub name: String,
+    /// The attributes.
+    pub attr: BTreeMap<String, AttrValue>,
+}
+
+/// A tensor shape.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TensorShapeProto {
+    /// The dimensions.
+    pub dim: Vec<Dimension>,
+    /// The flag indicating that the rank is unknown.
+    pub unknown_rank: bool,
+}
+
+/// A dimension of a tensor shape.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Dimension {
+    /// The size, which is -1 if unknown.
+    pub size: i64,
+    /// The name.
+    pub name: String,
+}
+
+/// A tensor.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TensorProto {
+    /// The data type.
+    pub dtype: DataType,
+    /// The shape.
+    pub tensor_shape: Option<TensorShapeProto>,
+    /// The version.
+    pub version_number: i32,
+    /// The content in the native encoding.
+    pub tensor_content: Vec<u8>,
+    /// The values of type `DT_HALF` and `DT_BFLOAT16` as bit patterns.
+    pub half_val: Vec<i32>,
+    /// The values of type `DT_FLOAT`.
+    pub float_val: Vec<f32>,
+    /// The values of type `DT_DOUBLE`.
+    pub double_val: Vec<f64>,
+    /// The values of types `DT_INT32`, `DT_INT16`, `DT_INT8`, and `DT_UINT8`.
+    pub int_val: Vec<i32>,
+    /// The values of type `DT_STRING`.
+    pub string_val: Vec<Vec<u8>>,
+    /// The values of type `DT_COMPLEX64` as interleaved parts.
+    pub scomplex_val: Vec<f32>,
+    /// The values of type `DT_INT64`.
+    pub int64_val: Vec<i64>,
+    /// The values of type `DT_BOOL`.
+    pub bool_val: Vec<bool>,
+    /// The values of type `DT_COMPLEX128` as interleaved parts.
+    pub dcomplex_val: Vec<f64>,
+    /// The values of type `DT_UINT32`.
+    pub uint32_val: Vec<u32>,
+    /// The values of type `DT_UINT64`.
+    pub uint64_val: Vec<u64>,
+}
+
+impl Graph {
+    /// Create a graph.
+    pub fn new() -> Result<Self> {
+        Ok(Graph { raw: nonnull!(ffi!(TF_NewGraph())) })
+    }
+
+    /// Find an operation by name.
+    pub fn find<'l>(&'l self, name: &str) -> Option<Operation<'l>> {
+        self.operation(name).ok().map(|raw| Operation { raw: raw, phantom: PhantomData })
+    }
+
+    /// Start building an operation of a particular type.
+    pub fn new_operation<'l>(&'l self, kind: &str, name: &str) -> Result<OperationBuilder<'l>> {
+        let (kind, name) = (try!(cstring(kind)), try!(cstring(name)));
+        Ok(OperationBuilder {
+            status: try!(Status::new()),
+            raw: nonnull!(ffi!(TF_NewOperation(self.raw, kind.as_ptr(), name.as_ptr()))),
+            phantom: PhantomData,
+        })
+    }
+
+    /// Import a graph definition.
+    ///
+    /// The nodes of the definition are added to the graph, which allows for
+    /// composing several graphs. A prefix can be used to avoid collisions
+    /// between names:
+    ///
+    /// ```
+    /// use tensorflux::graph::{Graph, ImportOptions};
+    /// use tensorflux::Buffer;
+    ///
+    /// macro_rules! ok(($result:expr) => ($result.unwrap()));
+    ///
+    /// let graph = ok!(Graph::new());
+    ///
+    /// let mut options = ImportOptions::default();
+    /// options.prefix = "model".into();
+    /// ok!(graph.import(&ok!(Buffer::load("examples/assets/multiplication.pb")), options));
+    ///
+    /// assert!(graph.find("model/c").is_some());
+    /// ```
+    pub fn import(&self, definition: &Buffer, options: ImportOptions) -> Result<()> {
+        let raw = RawImportOptions(nonnull!(ffi!(TF_NewImportGraphDefOptions())));
+        ffi!(TF_ImportGraphDefOptionsSetPrefix(raw.0, try!(cstring(&options.prefix)).as_ptr()));
+        let status = try!(Status::new());
+        ok!(ffi!(TF_GraphImportGraphDef(self.raw, definition.as_raw(), raw.0, status.as_raw())),
+            &status);
+        Ok(())
+    }
+
+    /// Export the graph as a graph definition.
+    ///
+    /// The result is a serialized `GraphDef`, which can be decoded using
+    /// `GraphDef::decode` or written to a file using `Buffer::write`.
+    pub fn to_graph_def(&self) -> Result<Buffer> {
+        let status = try!(Status::new());
+        let mut buffer = Buffer::new(vec![]);
+        ok!(ffi!(TF_GraphToGraphDef(self.raw, buffer.as_raw(), status.as_raw())), &status);
+        unsafe { buffer.reset() };
+        Ok(buffer)
+    }
+
+    /// Add a placeholder.
+    pub fn placeholder<'l>(&'l self, name: &str, data_type: DataType) -> Result<Operation<'l>> {
+        let mut builder = try!(self.new_operation("Placeholder", name));
+        try!(builder.set_attribute("dtype", &AttrValue::Type(data_type)));
+        builder.finish()
+    }
+
+    /// Add a constant.
+    pub fn constant<'l, T>(&'l self, name: &str, tensor: &Tensor<T>) -> Result<Operation<'l>>
+        where T: Value
+    {
+        let mut builder = try!(self.new_operation("Const", name));
+        try!(builder.set_attribute("dtype", &AttrValue::Type(T::data_type())));
+        try!(builder.set_attribute_tensor("value", tensor));
+        builder.finish()
+    }
+
+    /// Add an element-wise addition.
+    #[inline]
+    pub fn add<'l, T, U>(&'l self, name: &str, x: T, y: U) -> Result<Operation<'l>>
+        where T: Into<Port<'l>>, U: Into<Port<'l>>
+    {
+        self.binary("Add", name, x.into(), y.into())
+    }
+
+    /// Add an element-wise multiplication.
+    #[inline]
+    pub fn mul<'l, T, U>(&'l self, name: &str, x: T, y: U) -> Result<Operation<'l>>
+        where T: Into<Port<'l>>, U: Into<Port<'l>>
+    {
+        self.binary("Mul", name, x.into(), y.into())
+    }
+
+    /// Add a matrix multiplication.
+    #[inline]
+    pub fn matmul<'l, T, U>(&'l self, name: &str, x: T, y: U) -> Result<Operation<'l>>
+        where T: Into<Port<'l>>, U: Into<Port<'l>>
+    {
+        self.binary("MatMul", name, x.into(), y.into())
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    pub fn as_raw(&self) -> *mut TF_Graph {
+        self.raw
+    }
+
+    #[doc(hidden)]
+    pub fn operation(&self, name: &str) -> Result<*mut TF_Operation> {
+        let operation = ffi!(TF_GraphOperationByName(self.raw, try!(cstring(name)).as_ptr()));
+        if operation.is_null() {
+            raise!("operation `{}` is not found in the graph", name);
+        }
+        Ok(operation)
+    }
+
+    /// Find an output of an operation given a name in the form `node:index`
+    /// or `node`, which refers to the first output.
+    #[doc(hidden)]
+    pub fn port(&self, name: &str) -> Result<TF_Port> {
+        let (name, index) = split(name);
+        Ok(TF_Port { operation: try!(self.operation(name)), index: index })
+    }
+
+    fn binary<'l>(&'l self, kind: &str, name: &str, x: Port<'l>, y: Port<'l>)
+                  -> Result<Operation<'l>> {
+
+        let mut builder = try!(self.new_operation(kind, name));
+        builder.add_input(x);
+        builder.add_input(y);
+        builder.finish()
+    }
+}
+
+unsafe impl Send for Graph {}
+
+unsafe impl Sync for Graph {}
+
+impl Drop for Graph {
+    #[inline]
+    fn drop(&mut self) {
+        ffi!(TF_DeleteGraph(self.raw));
+    }
+}
+
+impl Drop for RawImportOptions {
+    #[inline]
+    fn drop(&mut self) {
+        ffi!(TF_DeleteImportGraphDefOptions(self.0));
+    }
+}
+
+impl<'l> Operation<'l> {
+    /// Return the name.
+    #[inline]
+    pub fn name(&self) -> String {
+        string(ffi!(TF_OperationName(self.raw)))
+    }
+
+    /// Return the type.
+    #[inline]
+    pub fn kind(&self) -> String {
+        string(ffi!(TF_OperationOpType(self.raw)))
+    }
+
+    /// Return the device.
+    #[inline]
+    pub fn device(&self) -> String {
+        string(ffi!(TF_OperationDevice(self.raw)))
+    }
+
+    /// Return the number of inputs.
+    #[inline]
+    pub fn inputs(&self) -> usize {
+        ffi!(TF_OperationNumInputs(self.raw)) as usize
+    }
+
+    /// Return the number of outputs.
+    #[inline]
+    pub fn outputs(&self) -> usize {
+        ffi!(TF_OperationNumOutputs(self.raw)) as usize
+    }
+
+    /// Return an output.
+    #[inline]
+    pub fn output(&self, index: usize) -> Port<'l> {
+        Port { operation: *self, index: index }
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    pub fn as_raw(&self) -> *mut TF_Operation {
+        self.raw
+    }
+}
+
+impl<'l> OperationBuilder<'l> {
+    /// Add an input.
+    #[inline]
+    pub fn add_input<T>(&mut self, input: T) where T: Into<Port<'l>> {
+        ffi!(TF_AddInput(self.raw, input.into().as_raw()));
+    }
+
+    /// Add a list of inputs, which is a single input of the operation.
+    pub fn add_input_list(&mut self, inputs: &[Port<'l>]) {
+        let inputs = inputs.iter().map(|input| input.as_raw()).collect::<Vec<_>>();
+        ffi!(TF_AddInputList(self.raw, inputs.as_ptr(), inputs.len() as c_int));
+    }
+
+    /// Add an operation that should be executed before this one.
+    #[inline]
+    pub fn add_control_input(&mut self, operation: Operation<'l>) {
+        ffi!(TF_AddControlInput(self.raw, operation.raw));
+    }
+
+    /// Set the device.
+    pub fn set_device(&mut self, device: &str) -> Result<()> {
+        ffi!(TF_SetDevice(self.raw, try!(cstring(device)).as_ptr()));
+        Ok(())
+    }
+
+    /// Set an attribute.
+    pub fn set_attribute(&mut self, name: &str, value: &AttrValue) -> Result<()> {
+        let value = value.encode();
+        ok!(ffi!(TF_SetAttrValueProto(self.raw, try!(cstring(name)).as_ptr(),
+                                      value.as_ptr() as *const _, value.len() as size_t,
+                                      self.status.as_raw())), &self.status);
+        Ok(())
+    }
+
+    /// Set an attribute to a tensor.
+    pub fn set_attribute_tensor<T>(&mut self, name: &str, tensor: &Tensor<T>) -> Result<()>
+        where T: Value
+    {
+        let name = try!(cstring(name));
+        let tensor = try!(tensor.to_raw());
+        ffi!(TF_SetAttrTensor(self.raw, name.as_ptr(), tensor, self.status.as_raw()));
+        ffi!(TF_DeleteTensor(tensor));
+        success!(&self.status);
+        Ok(())
+    }
+
+    /// Add the operation to the graph.
+    pub fn finish(self) -> Result<Operation<'l>> {
+        let operation = nonnull!(ffi!(TF_FinishOperation(self.raw, self.status.as_raw())),
+                                 &self.status);
+        Ok(Operation { raw: operation, phantom: PhantomData })
+    }
+}
+
+impl<'l> Port<'l> {
+    #[doc(hidden)]
+    #[inline]
+    pub fn as_raw(&self) -> TF_Port {
+        TF_Port { operation: self.operation.raw, index: self.index as c_int }
+    }
+}
+
+impl<'l> From<Operation<'l>> for Port<'l> {
+    #[inline]
+    fn from(operation: Operation<'l>) -> Self {
+        operation.output(0)
+    }
+}
+
+impl GraphDef {
+    /// Find a node by name.
+    pub fn find(&self, name: &str) -> Option<&NodeDef> {
+        self.node.iter().find(|node| node.name == name)
+    }
+
+    /// Return the operations used in the graph.
+    pub fn operations(&self) -> BTreeSet<&str> {
+        self.node.iter().map(|node| &node.op[..]).collect()
+    }
+
+    /// Return the nodes whose outputs are not consumed by other nodes.
+    ///
+    /// Such nodes are the natural candidates for outputs and targets.
+    pub fn outputs(&self) -> Vec<&NodeDef> {
+        let consumed = self.node.iter().flat_map(|node| node.input.iter())
+                                       .map(|input| input.trim_start_matches('^'))
+                                       .map(|input| input.split(':').next().unwrap())
+                                       .collect::<BTreeSet<_>>();
+        self.node.iter().filter(|node| !consumed.contains(&node.name[..])).collect()
+    }
+
+    /// Return the nodes that are placeholders.
+    ///
+    /// Such nodes are the natural candidates for inputs.
+    pub fn placeholders(&self) -> Vec<&NodeDef> {
+        self.node.iter().filter(|node| node.op.starts_with("Placeholder")).collect()
+    }
+}
+
+impl TensorShapeProto {
+    /// Check if the shape is compatible with particular dimensions.
+    ///
+    /// Dimensions of unknown size match any size, and a shape of unknown rank
+    /// matches any dimensions.
+    pub fn matches(&self, dimensions: &[usize]) -> bool {
+        if self.unknown_rank {
+            return true;
+        }
+        self.dim.len() == dimensions.len() &&
+        self.dim.iter().zip(dimensions).all(|(dimension, &size)| {
+            dimension.size < 0 || dimension.size as usize == size
+        })
+    }
+}
+
+impl Codec for GraphDef {
+    fn read(&mut self, field: u32, wire: Wire, reader: &mut Reader) -> Result<()> {
+        match field {
+            1 => self.node.push(try!(reader.message(wire))),
+            2 => self.library = try!(reader.bytes(wire)),
+            4 => self.versions = Some(try!(reader.message(wire))),
+            _ => try!(reader.skip(wire)),
+        }
+        Ok(())
+    }
+
+    fn write(&self, writer: &mut Writer) {
+        for node in &self.node {
+            writer.message(1, node);
+        }
+        if !self.library.is_empty() {
+            writer.delimited(2, &self.library);
+        }
+        if let Some(ref versions) = self.versions {
+            writer.message(4, versions);
+        }
+    }
+}
+
+impl Codec for VersionDef {
+    fn read(&mut self, field: u32, wire: Wire, reader: &mut Reader) -> Result<()> {
+        match field {
+            1 => self.producer = try!(reader.int32(wire)),
+            2 => self.min_consumer = try!(reader.int32(wire)),
+            3 => try!(reader.repeated(wire, Wire::Varint, &mut self.bad_consumers, Reader::int32)),
+            _ => try!(reader.skip(wire)),
+        }
+        Ok(())
+    }
+
+    fn write(&self, writer: &mut Writer) {
+        writer.int32(1, self.producer);
+        writer.int32(2, self.min_consumer);
+        writer.packed(3, &self.bad_consumers, |writer, &value| {
+            writer.raw_varint(value as i64 as u64)
+        });
+    }
+}
+
+impl Codec for NodeDef {
+    fn read(&mut self, field: u32, wire: Wire, reader: &mut Reader) -> Result<()> {
+        match field {
+            1 => self.name = try!(reader.string(wire)),
+            2 => self.op = try!(reader.string(wire)),
+            3 => self.input.push(try!(reader.string(wire))),
+            4 => self.device = try!(reader.string(wire)),
+            5 => try!(reader.entry(wire, &mut self.attr, Reader::message)),
+            _ => try!(reader.skip(wire)),
+        }
+        Ok(())
+    }
+
+    fn write(&self, writer: &mut Writer) {
+        writer.string(1, &self.name);
+        writer.string(2, &self.op);
+        for input in &self.input {
+            writer.delimited(3, input.as_bytes());
+        }
+        writer.string(4, &self.device);
+        for (key, value) in &self.attr {
+            writer.entry(5, key, |writer| writer.message(2, value));
+        }
+    }
+}
+
+impl Default for AttrValue {
+    #[inline]
+    fn default() -> Self {
+        AttrValue::Empty
+    }
+}
+
+impl Codec for AttrValue {
+    fn read(&mut self, field: u32, wire: Wire, reader: &mut Reader) -> Result<()> {
+        *self = match field {
+            1 => AttrValue::List(try!(reader.message(wire))),
+            2 => AttrValue::String(try!(reader.bytes(wire))),
+            3 => AttrValue::Int(try!(reader.int64(wire))),
+            4 => AttrValue::Float(try!(reader.float(wire))),
+            5 => AttrValue::Bool(try!(reader.bool(wire))),
+            6 => AttrValue::Type(DataType::from_i32(try!(reader.int32(wire)))),
+            7 => AttrValue::Shape(try!(reader.message(wire))),
+            8 => AttrValue::Tensor(try!(reader.message(wire))),
+            9 => AttrValue::Placeholder(try!(reader.string(wire))),
+            10 => AttrValue::Function(try!(reader.message(wire))),
+            _ => return reader.skip(wire),
+        };
+        Ok(())
+    }
+
+    fn write(&self, writer: &mut Writer) {
+        match *self {
+            AttrValue::Empty => {},
+            AttrValue::List(ref value) => writer.message(1, value),
+            AttrValue::String(ref value) => writer.delimited(2, value),
+            AttrValue::Int(value) => writer.varint(3, value as u64),
+            AttrValue::Float(value) => writer.fixed32(4, value.to_bits()),
+            AttrValue::Bool(value) => writer.varint(5, value as u64),
+            AttrValue::Type(value) => writer.varint(6, value.to_i32() as i64 as u64),
+            AttrValue::Shape(ref value) => writer.message(7, value),
+            AttrValue::Tensor(ref value) => writer.message(8, value),
+            AttrValue::Placeholder(ref value) => writer.delimited(9, value.as_bytes()),
+            AttrValue::Function(ref value) => writer.message(10, value),
+        }
+    }
+}
+
+impl Codec for AttrList {
+    fn read(&mut self, field: u32, wire: Wire, reader: &mut Reader) -> Result<()> {
+        match field {
+            2 => self.s.push(try!(reader.bytes(wire))),
+            3 => try!(reader.repeated(wire, Wire::Varint, &mut self.i, Reader::int64)),
+            4 => try!(reader.repeated(wire, Wire::Fixed32, &mut self.f, Reader::float)),
+            5 => try!(reader.repeated(wire, Wire::Varint, &mut self.b, Reader::bool)),
+            6 => try!(reader.repeated(wire, Wire::Varint, &mut self.type_, |reader, wire| {
+                reader.int32(wire).map(DataType::from_i32)
+            })),
+            7 => self.shape.push(try!(reader.message(wire))),
+            8 => self.tensor.push(try!(reader.message(wire))),
+            9 => self.func.push(try!(reader.message(wire))),
+            _ => try!(reader.skip(wire)),
+        }
+        Ok(())
+    }
+
+    fn write(&self, writer: &mut Writer) {
+        for value in &self.s {
+            writer.delimited(2, value);
+        }
+        writer.packed(3, &self.i, |writer, &value| writer.raw_varint(value as u64));
+        writer.packed(4, &self.f, |writer, &value| writer.raw_fixed32(value.to_bits()));
+        writer.packed(5, &self.b, |writer, &value| writer.raw_varint(value as u64));
+        writer.packed(6, &self.type_, |writer, value| {
+            writer.raw_varint(value.to_i32() as i64 as u64)
+        });
+        for value in &self.shape {
+            writer.message(7, value);
+        }
+        for value in &self.tensor {
+            writer.message(8, value);
+        }
+        for value in &self.func {
+            writer.message(9, value);
+        }
+    }
+}
+
+impl Codec for NameAttrList {
+    fn read(&mut self, field: u32, wire: Wire, reader: &mut Reader) -> Result<()> {
+        match field {
+            1 => self.name = try!(reader.string(wire)),
+            2 => try!(reader.entry(wire, &mut self.attr, Reader::message)),
+            _ => try!(reader.skip(wire)),
+        }
+        Ok(())
+    }
+
+    fn write(&self, writer: &mut Writer) {
+        writer.string(1, &self.name);
+        for (key, value) in &self.attr {
+            writer.entry(2, key, |writer| writer.message(2, value));
+        }
+    }
+}
+
+impl Codec for TensorShapeProto {
+    fn read(&mut self, field: u32, wire: Wire, reader: &mut Reader) -> Result<()> {
+        match field {
+            2 => self.dim.push(try!(reader.message(wire))),
+            3 => self.unknown_rank = try!(reader.bool(wire)),
+            _ => try!(reader.skip(wire)),
+        }
+        Ok(())
+    }
+
+    fn write(&self, writer: &mut Writer) {
+        for dimension in &self.dim {
+            writer.message(2, dimension);
+        }
+        writer.bool(3, self.unknown_rank);
+    }
+}
+
+impl Codec for Dimension {
+    fn read(&mut self, field: u32, wire: Wire, reader: &mut Reader) -> Result<()> {
+        match field {
+            1 => self.size = try!(reader.int64(wire)),
+            2 => self.name = try!(reader.string(wire)),
+            _ => try!(reader.skip(wire)),
+        }
+        Ok(())
+    }
+
+    fn write(&self, writer: &mut Writer) {
+        writer.int64(1, self.size);
+        writer.string(2, &self.name);
+    }
+}
+
+impl Codec for TensorProto {
+    fn read(&mut self, field: u32, wire: Wire, reader: &mut Reader) -> Result<()> {
+        match field {
+            1 => self.dtype = DataType::from_i32(try!(reader.int32(wire))),
+            2 => self.tensor_shape = Some(try!(reader.message(wire))),
+            3 => self.version_number = try!(reader.int32(wire)),
+            4 => self.tensor_content = try!(reader.bytes(wire)),
+            5 => try!(reader.repeated(wire, Wire::Fixed32, &mut self.float_val, Reader::float)),
+            6 => try!(reader.repeated(wire, Wire::Fixed64, &mut self.double_val, Reader::double)),
+            7 => try!(reader.repeated(wire, Wire::Varint, &mut self.int_val, Reader::int32)),
+            8 => self.string_val.push(try!(reader.bytes(wire))),
+            9 => try!(reader.repeated(wire, Wire::Fixed32, &mut self.scomplex_val, Reader::float)),
+            10 => try!(reader.repeated(wire, Wire::Varint, &mut self.int64_val, Reader::int64)),
+            11 => try!(reader.repeated(wire, Wire::Varint, &mut self.bool_val, Reader::bool)),
+            12 => try!(reader.repeated(wire, Wire::Fixed64, &mut self.dcomplex_val,
+                                       Reader::double)),
+            13 => try!(reader.repeated(wire, Wire::Varint, &mut self.half_val, Reader::int32)),
+            16 => try!(reader.repeated(wire, Wire::Varint, &mut self.uint32_val, Reader::uint32)),
+            17 => try!(reader.repeated(wire, Wire::Varint, &mut self.uint64_val, Reader::uint64)),
+            _ => try!(reader.skip(wire)),
+        }
+        Ok(())
+    }
+
+    fn write(&self, writer: &mut Writer) {
+        writer.int32(1, self.dtype.to_i32());
+        if let Some(ref shape) = self.tensor_shape {
+            writer.message(2, shape);
+        }
+        writer.int32(3, self.version_number);
+        writer.bytes(4, &self.tensor_content);
+        writer.packed(5, &self.float_val, |writer, &value| writer.raw_fixed32(value.to_bits()));
+        writer.packed(6, &self.double_val, |writer, &value| writer.raw_fixed64(value.to_bits()));
+        writer.packed(7, &self.int_val, |writer, &value| writer.raw_varint(value as i64 as u64));
+        for value in &self.string_val {
+            writer.delimited(8, value);
+        }
+        writer.packed(9, &self.scomplex_val, |writer, &value| {
+            writer.raw_fixed32(value.to_bits())
+        });
+        writer.packed(10, &self.int64_val, |writer, &value| writer.raw_varint(value as u64));
+        writer.packed(11, &self.bool_val, |writer, &value| writer.raw_varint(value as u64));
+        writer.packed(12, &self.dcomplex_val, |writer, &value| {
+            writer.raw_fixed64(value.to_bits())
+        });
+        writer.packed(13, &self.half_val, |writer, &value| writer.raw_varint(value as i64 as u64));
+        writer.packed(16, &self.uint32_val, |writer, &value| writer.raw_varint(value as u64));
+        writer.packed(17, &self.uint64_val, |writer, &value| writer.raw_varint(value));
+    }
+}
+
+fn string(pointer: *const c_char) -> String {
+    if pointer.is_null() {
+        return String::new();
+    }
+    unsafe { CStr::from_ptr(pointer) }.to_string_lossy().into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use std::io::Read;
+
+    use buffer::Buffer;
+    use graph::{AttrValue, Dimension, GraphDef, TensorShapeProto};
+    use kind::DataType;
+    use protobuf::Message;
+
+    #[test]
+    fn decode_encode() {
+        let mut data = vec![];
+        let mut file = File::open("examples/assets/multiplication.pb").unwrap();
+        file.read_to_end(&mut data).unwrap();
+
+        let definition = GraphDef::decode(&data).unwrap();
+        let names = definition.placeholders().iter().map(|node| &node.name[..])
+                                                    .collect::<Vec<_>>();
+        assert_eq!(names, vec!["a", "b"]);
+        let names = definition.outputs().iter().map(|node| &node.name[..]).collect::<Vec<_>>();
+        assert_eq!(names, vec!["c"]);
+        let node = definition.find("c").unwrap();
+        assert_eq!(node.input, vec!["a", "b"]);
+        assert_eq!(node.attr.get("T"), Some(&AttrValue::Type(DataType::Float)));
+
+        assert_eq!(GraphDef::decode(&definition.encode()).unwrap(), definition);
+        let buffer = Buffer::from(&definition);
+        assert_eq!(GraphDef::from_buffer(&buffer).unwrap(), definition);
+    }
+
+    #[test]
+    fn matches() {
+        let mut shape = TensorShapeProto::default();
+        shape.dim.push(Dimension { size: -1, name: String::new() });
+        shape.dim.push(Dimension { size: 3, name: String::new() });
+        assert!(shape.matches(&[42, 3]));
+        assert!(!shape.matches(&[42, 4]));
+        assert!(!shape.matches(&[3]));
+        shape.unknown_rank = true;
+        assert!(shape.matches(&[3]));
+    }
+}
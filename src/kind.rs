@@ -0,0 +1,151 @@
+use ffi::TF_DataType;
+use std::fmt;
+
+/// A data type.
+///
+/// The numeric values of the variants correspond to the `DataType` enum of
+/// TensorFlow’s protocol buffers. The variants cover the data types of the C
+/// API of TensorFlow 0.11; the ones added later, such as resources, variants,
+/// and 32- and 64-bit unsigned integers, are reported as `Other`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DataType {
+    /// The absence of a data type.
+    Invalid,
+    /// 32-bit floating-point numbers.
+    Float,
+    /// 64-bit floating-point numbers.
+    Double,
+    /// 32-bit signed integers.
+    Int32,
+    /// 8-bit unsigned integers.
+    UInt8,
+    /// 16-bit signed integers.
+    Int16,
+    /// 8-bit signed integers.
+    Int8,
+    /// Byte strings.
+    String,
+    /// Complex numbers with 32-bit parts.
+    Complex64,
+    /// 64-bit signed integers.
+    Int64,
+    /// Booleans.
+    Bool,
+    /// Quantized 8-bit signed integers.
+    QInt8,
+    /// Quantized 8-bit unsigned integers.
+    QUInt8,
+    /// Quantized 32-bit signed integers.
+    QInt32,
+    /// Truncated 16-bit floating-point numbers.
+    BFloat16,
+    /// Quantized 16-bit signed integers.
+    QInt16,
+    /// Quantized 16-bit unsigned integers.
+    QUInt16,
+    /// 16-bit unsigned integers.
+    UInt16,
+    /// Complex numbers with 64-bit parts.
+    Complex128,
+    /// 16-bit floating-point numbers.
+    Half,
+    /// A data type not known to this crate, such as a resource or reference
+    /// type.
+    Other(i32),
+}
+
+macro_rules! implement {
+    ($($variant:ident => $raw:ident => $code:expr => $name:expr,)*) => {
+        impl DataType {
+            /// Create a data type from its numeric value.
+            pub fn from_i32(value: i32) -> Self {
+                match value {
+                    0 => DataType::Invalid,
+                    $($code => DataType::$variant,)*
+                    _ => DataType::Other(value),
+                }
+            }
+
+            /// Return the numeric value.
+            pub fn to_i32(&self) -> i32 {
+                match *self {
+                    DataType::Invalid => 0,
+                    $(DataType::$variant => $code,)*
+                    DataType::Other(value) => value,
+                }
+            }
+
+            #[doc(hidden)]
+            pub fn from_raw(raw: TF_DataType) -> Self {
+                match raw {
+                    $(TF_DataType::$raw => DataType::$variant,)*
+                }
+            }
+
+            #[doc(hidden)]
+            pub fn to_raw(&self) -> Option<TF_DataType> {
+                match *self {
+                    $(DataType::$variant => Some(TF_DataType::$raw),)*
+                    _ => None,
+                }
+            }
+        }
+
+        impl fmt::Display for DataType {
+            fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                match *self {
+                    DataType::Invalid => "invalid".fmt(formatter),
+                    $(DataType::$variant => $name.fmt(formatter),)*
+                    DataType::Other(value) => write!(formatter, "unknown ({})", value),
+                }
+            }
+        }
+    }
+}
+
+implement! {
+    Float => TF_FLOAT => 1 => "float32",
+    Double => TF_DOUBLE => 2 => "float64",
+    Int32 => TF_INT32 => 3 => "int32",
+    UInt8 => TF_UINT8 => 4 => "uint8",
+    Int16 => TF_INT16 => 5 => "int16",
+    Int8 => TF_INT8 => 6 => "int8",
+    String => TF_STRING => 7 => "string",
+    Complex64 => TF_COMPLEX64 => 8 => "complex64",
+    Int64 => TF_INT64 => 9 => "int64",
+    Bool => TF_BOOL => 10 => "bool",
+    QInt8 => TF_QINT8 => 11 => "qint8",
+    QUInt8 => TF_QUINT8 => 12 => "quint8",
+    QInt32 => TF_QINT32 => 13 => "qint32",
+    BFloat16 => TF_BFLOAT16 => 14 => "bfloat16",
+    QInt16 => TF_QINT16 => 15 => "qint16",
+    QUInt16 => TF_QUINT16 => 16 => "quint16",
+    UInt16 => TF_UINT16 => 17 => "uint16",
+    Complex128 => TF_COMPLEX128 => 18 => "complex128",
+    Half => TF_HALF => 19 => "float16",
+}
+
+impl Default for DataType {
+    #[inline]
+    fn default() -> Self {
+        DataType::Invalid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use kind::DataType;
+
+    #[test]
+    fn from_i32() {
+        for value in 0..30 {
+            assert_eq!(DataType::from_i32(value).to_i32(), value);
+        }
+        assert_eq!(DataType::from_i32(1), DataType::Float);
+        assert_eq!(DataType::from_i32(20), DataType::Other(20));
+        assert_eq!(DataType::from_i32(22), DataType::Other(22));
+        assert_eq!(DataType::from_i32(23), DataType::Other(23));
+        assert_eq!(DataType::from_i32(101), DataType::Other(101));
+        assert_eq!(DataType::Float.to_string(), "float32");
+    }
+}
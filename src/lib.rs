@@ -45,25 +45,52 @@ extern crate tensorflux_sys as ffi;
 #[cfg(feature = "complex")]
 extern crate num_complex as num;
 
+#[cfg(feature = "half")]
+extern crate half;
+
+#[cfg(feature = "ndarray")]
+extern crate ndarray;
+
 #[macro_use]
 mod macros;
 
+mod batcher;
 mod buffer;
+mod config;
 mod error;
+#[cfg(feature = "async")]
+mod executor;
+mod kind;
 mod memory;
 mod options;
+mod protobuf;
+mod quantized;
+mod run;
 mod session;
 mod status;
+mod string;
 mod library;
 mod tensor;
 mod value;
 
+pub mod graph;
+pub mod model;
+pub mod stats;
+
+pub use batcher::{BatchOptions, Batcher};
 pub use buffer::Buffer;
-pub use error::Error;
+pub use config::{Config, GraphOptions, JitLevel, OptimizerLevel, OptimizerOptions};
+pub use error::{Code, Error, ErrorKind};
+#[cfg(feature = "async")]
+pub use executor::Task;
+pub use kind::DataType;
 pub use library::Library;
 pub use options::Options;
-pub use session::{Input, Output, Session, Target};
-pub use tensor::Tensor;
+pub use protobuf::Message;
+pub use quantized::{QInt16, QInt32, QInt8, QUInt16, QUInt8};
+pub use run::{RunMetadata, RunOptions, TraceLevel};
+pub use session::{Input, Output, Outputs, PartialRun, RunBuilder, Session, Target};
+pub use tensor::{AnyTensor, Rows, Tensor};
 pub use value::Value;
 
 /// A result.
@@ -78,3 +105,6 @@ pub type c32 = num::Complex<f32>;
 #[cfg(feature = "complex")]
 #[allow(non_camel_case_types)]
 pub type c64 = num::Complex<f64>;
+
+#[cfg(feature = "half")]
+pub use half::{bf16, f16};
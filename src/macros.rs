@@ -1,40 +1,3 @@
-macro_rules! deref {
-    ($kind:ident::$field:ident<T>) => (
-        impl<T> ::std::ops::Deref for $kind<T> {
-            type Target = [T];
-
-            #[inline]
-            fn deref(&self) -> &[T] {
-                &self.$field
-            }
-        }
-
-        impl<T> ::std::ops::DerefMut for $kind<T> {
-            #[inline]
-            fn deref_mut(&mut self) -> &mut [T] {
-                &mut self.$field
-            }
-        }
-    );
-    ($kind:ident::$field:ident<$element:ident>) => (
-        impl ::std::ops::Deref for $kind {
-            type Target = [$element];
-
-            #[inline]
-            fn deref(&self) -> &[$element] {
-                &self.$field
-            }
-        }
-
-        impl ::std::ops::DerefMut for $kind {
-            #[inline]
-            fn deref_mut(&mut self) -> &mut [$element] {
-                &mut self.$field
-            }
-        }
-    );
-}
-
 macro_rules! ffi(
     ($function:ident($($argument:expr),*)) => (unsafe { ::ffi::$function($($argument),*) });
 );
@@ -45,36 +8,26 @@ macro_rules! into_cstring(
 
 macro_rules! memory {
     ($kind:ident<T>) => (
-        deref!($kind::memory<T>);
+        impl<T> ::std::ops::Deref for $kind<T> {
+            type Target = [T];
 
-        impl<T> ::std::convert::AsRef<[T]> for $kind<T> {
             #[inline]
-            fn as_ref(&self) -> &[T] {
+            fn deref(&self) -> &[T] {
                 &self.memory
             }
         }
 
-        impl<T> Into<Vec<T>> for $kind<T> where T: Clone {
-            #[inline]
-            fn into(mut self) -> Vec<T> {
-                self.memory.empty()
-            }
-        }
-    );
-    ($kind:ident<$element:ident>) => (
-        deref!($kind::memory<$element>);
-
-        impl ::std::convert::AsRef<[$element]> for $kind {
+        impl<T> ::std::convert::AsRef<[T]> for $kind<T> {
             #[inline]
-            fn as_ref(&self) -> &[$element] {
+            fn as_ref(&self) -> &[T] {
                 &self.memory
             }
         }
 
-        impl Into<Vec<$element>> for $kind {
+        impl<T> Into<Vec<T>> for $kind<T> where T: Clone {
             #[inline]
-            fn into(mut self) -> Vec<$element> {
-                self.memory.empty()
+            fn into(self) -> Vec<T> {
+                self.memory.into()
             }
         }
     );
@@ -85,14 +38,14 @@ macro_rules! nonnull(
         let pointer = $pointer;
         if pointer.is_null() {
             success!($status);
-            raise!("failed to call TensorFlow");
+            return Err(::error::Error::from_null());
         }
         pointer
     });
     ($pointer:expr) => ({
         let pointer = $pointer;
         if pointer.is_null() {
-            raise!("failed to call TensorFlow");
+            return Err(::error::Error::from_null());
         }
         pointer
     });
@@ -106,7 +59,7 @@ macro_rules! ok(
     });
     ($result:expr) => (match $result {
         Ok(result) => result,
-        Err(error) => raise!(error.to_string()),
+        Err(error) => return Err(::error::Error::from_io(error)),
     });
 );
 
@@ -115,6 +68,13 @@ macro_rules! raise(
     ($message:expr) => (return Err(::error::Error::from($message)));
 );
 
+macro_rules! recover(
+    ($result:expr) => (match $result {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    });
+);
+
 macro_rules! success(
     ($status:expr) => (
         if let Some(error) = ::error::Error::from_status($status) {
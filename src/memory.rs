@@ -1,55 +1,186 @@
+use ffi::TF_Tensor;
+use libc::{c_void, size_t};
 use std::convert::AsRef;
-use std::mem;
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+use std::{mem, slice};
 
+/// A chunk of memory owned either by Rust or by TensorFlow.
+///
+/// The data are reference counted so that TensorFlow can be given access to
+/// them without copying: each handle passed to TensorFlow holds a reference,
+/// which is released by the deallocator. Mutable access to data that are
+/// shared or owned by TensorFlow results in a copy.
 pub struct Memory<T> {
-    data: Vec<T>,
-    owned: bool,
+    data: Arc<Data<T>>,
+}
+
+pub type Deallocator = unsafe extern "C" fn(*mut c_void, size_t);
+
+enum Data<T> {
+    Owned(Vec<T>),
+    Foreign(Foreign<T>),
+}
+
+struct Foreign<T> {
+    pointer: *mut T,
+    length: usize,
+    release: Release,
+}
+
+enum Release {
+    Nothing,
+    Buffer(Deallocator),
+    Tensor(*mut TF_Tensor),
 }
 
 impl<T> Memory<T> {
     #[inline]
     pub fn new(data: Vec<T>) -> Self {
-        Memory { data: data, owned: true }
+        Memory { data: Arc::new(Data::Owned(data)) }
     }
 
+    /// Wrap data that outlive the memory.
     #[inline]
     pub unsafe fn from_raw_parts(pointer: *mut T, length: usize) -> Self {
-        Memory { data: Vec::from_raw_parts(pointer, length, length), owned: false }
+        Memory::foreign(pointer, length, Release::Nothing)
+    }
+
+    /// Wrap data that should be freed by a deallocator.
+    #[inline]
+    pub unsafe fn from_buffer(pointer: *mut T, length: usize, deallocator: Option<Deallocator>)
+                              -> Self {
+        Memory::foreign(pointer, length, match deallocator {
+            Some(deallocator) => Release::Buffer(deallocator),
+            _ => Release::Nothing,
+        })
+    }
+
+    /// Wrap data that belong to a tensor, which is deleted with the memory.
+    #[inline]
+    pub unsafe fn from_tensor(raw: *mut TF_Tensor, pointer: *mut T, length: usize) -> Self {
+        Memory::foreign(pointer, length, Release::Tensor(raw))
+    }
+
+    /// Share the data with TensorFlow.
+    ///
+    /// The returned argument should be passed to `release` once TensorFlow is
+    /// done with the data.
+    #[inline]
+    pub fn share(&self) -> *mut c_void {
+        Arc::into_raw(self.data.clone()) as *mut _
+    }
+
+    #[inline]
+    unsafe fn foreign(pointer: *mut T, length: usize, release: Release) -> Self {
+        let foreign = Foreign { pointer: pointer, length: length, release: release };
+        Memory { data: Arc::new(Data::Foreign(foreign)) }
+    }
+}
+
+unsafe impl<T> Send for Memory<T> where T: Send + Sync {}
+
+unsafe impl<T> Sync for Memory<T> where T: Send + Sync {}
+
+impl<T> Clone for Memory<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Memory { data: self.data.clone() }
     }
+}
+
+impl<T> Deref for Memory<T> {
+    type Target = [T];
 
-    pub fn empty(&mut self) -> Vec<T> where T: Clone {
-        if self.owned {
-            mem::replace(&mut self.data, vec![])
-        } else {
-            let data = self.data.clone();
-            mem::forget(mem::replace(&mut self.data, vec![]));
-            self.owned = true;
-            data
+    #[inline]
+    fn deref(&self) -> &[T] {
+        match *self.data {
+            Data::Owned(ref data) => data,
+            Data::Foreign(ref foreign) => unsafe { view(foreign.pointer, foreign.length) },
         }
     }
 }
 
-deref!(Memory::data<T>);
+impl<T> DerefMut for Memory<T> where T: Clone {
+    fn deref_mut(&mut self) -> &mut [T] {
+        let unique = match Arc::get_mut(&mut self.data) {
+            Some(&mut Data::Owned(_)) => true,
+            _ => false,
+        };
+        if !unique {
+            let data = self.to_vec();
+            self.data = Arc::new(Data::Owned(data));
+        }
+        match Arc::get_mut(&mut self.data) {
+            Some(&mut Data::Owned(ref mut data)) => data,
+            _ => unreachable!(),
+        }
+    }
+}
 
 impl<T> AsRef<[T]> for Memory<T> {
     #[inline]
     fn as_ref(&self) -> &[T] {
-        &self.data
+        self
     }
 }
 
-impl<T> Drop for Memory<T> {
-    #[inline]
+impl<T> Into<Vec<T>> for Memory<T> where T: Clone {
+    fn into(self) -> Vec<T> {
+        match Arc::try_unwrap(self.data) {
+            Ok(Data::Owned(data)) => data,
+            Ok(data) => Memory { data: Arc::new(data) }.to_vec(),
+            Err(data) => Memory { data: data }.to_vec(),
+        }
+    }
+}
+
+impl<T> Drop for Foreign<T> {
     fn drop(&mut self) {
-        if !self.owned {
-            mem::forget(mem::replace(&mut self.data, vec![]));
+        match self.release {
+            Release::Nothing => {},
+            Release::Buffer(deallocator) => unsafe {
+                let size = self.length * mem::size_of::<T>();
+                deallocator(self.pointer as *mut _, size as size_t);
+            },
+            Release::Tensor(raw) => ffi!(TF_DeleteTensor(raw)),
         }
     }
 }
 
-impl<T> Into<Vec<T>> for Memory<T> where T: Clone {
-    #[inline]
-    fn into(mut self) -> Vec<T> {
-        self.empty()
+/// Release the data shared with TensorFlow.
+pub unsafe extern "C" fn release<T>(_: *mut c_void, _: size_t, argument: *mut c_void) {
+    drop(Arc::from_raw(argument as *const Data<T>));
+}
+
+#[inline]
+unsafe fn view<'l, T>(pointer: *mut T, length: usize) -> &'l [T] {
+    if length == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(pointer, length)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use memory::{Memory, release};
+    use std::ptr;
+
+    #[test]
+    fn share() {
+        let mut memory = Memory::new(vec![1, 2, 3]);
+        let pointer = memory.as_ptr();
+
+        let argument = memory.share();
+        unsafe { release::<i32>(ptr::null_mut(), 0, argument) };
+        memory[0] = 42;
+        assert_eq!(memory.as_ptr(), pointer);
+
+        let argument = memory.share();
+        memory[1] = 69;
+        assert!(memory.as_ptr() != pointer);
+        assert_eq!(&memory[..], &[42, 69, 3]);
+        unsafe { release::<i32>(ptr::null_mut(), 0, argument) };
     }
 }
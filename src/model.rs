@@ -0,0 +1,222 @@
+//! Meta graphs.
+//!
+//! The types in this module mirror the protocol buffers that TensorFlow uses
+//! to describe exported models, and they can be found in TensorFlow’s
+//! [repository][1]. A meta graph exported by TensorFlow, such as a `.meta`
+//! file written by a saver, can be decoded, and its graph can be used to
+//! extend a session:
+//!
+//! ```no_run
+//! use tensorflux::{Buffer, Message, Options, Session};
+//! use tensorflux::model::MetaGraphDef;
+//!
+//! let definition = MetaGraphDef::decode(&Buffer::load("model.meta").unwrap()).unwrap();
+//! let mut session = Session::new(&Options::new().unwrap()).unwrap();
+//! if let Some(ref graph) = definition.graph_def {
+//!     session.extend(&Buffer::from(graph)).unwrap();
+//! }
+//! for (key, signature) in &definition.signature_def {
+//!     println!("{}: {}", key, signature.method_name);
+//! }
+//! ```
+//!
+//! Saved models cannot be loaded directly, since the C API of TensorFlow 0.11,
+//! which the bindings target, provides no loader.
+//!
+//! [1]: https://github.com/tensorflow/tensorflow/blob/master/tensorflow/core/protobuf/meta_graph.proto
+
+use std::collections::BTreeMap;
+
+use Result;
+use graph::{GraphDef, TensorShapeProto};
+use kind::DataType;
+use protobuf::{Codec, Reader, Wire, Writer};
+
+/// A meta-graph definition.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MetaGraphDef {
+    /// The meta information.
+    pub meta_info_def: Option<MetaInfoDef>,
+    /// The graph definition.
+    pub graph_def: Option<GraphDef>,
+    /// The saver as a serialized `SaverDef`.
+    pub saver_def: Vec<u8>,
+    /// The collections as serialized `CollectionDef`s.
+    pub collection_def: BTreeMap<String, Vec<u8>>,
+    /// The signatures.
+    pub signature_def: BTreeMap<String, SignatureDef>,
+    /// The asset files as serialized `AssetFileDef`s.
+    pub asset_file_def: Vec<Vec<u8>>,
+}
+
+/// Meta information about a meta graph.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MetaInfoDef {
+    /// The version specified by the user.
+    pub meta_graph_version: String,
+    /// The operations used as a serialized `OpList`.
+    pub stripped_op_list: Vec<u8>,
+    /// The tags identifying the meta graph.
+    pub tags: Vec<String>,
+    /// The version of TensorFlow used to create the meta graph.
+    pub tensorflow_version: String,
+    /// The Git version of TensorFlow used to create the meta graph.
+    pub tensorflow_git_version: String,
+}
+
+/// A signature.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SignatureDef {
+    /// The inputs indexed by their logical names.
+    pub inputs: BTreeMap<String, TensorInfo>,
+    /// The outputs indexed by their logical names.
+    pub outputs: BTreeMap<String, TensorInfo>,
+    /// The name of the method.
+    pub method_name: String,
+}
+
+/// Information about a tensor.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TensorInfo {
+    /// The name of the tensor in the form `node:index`.
+    pub name: String,
+    /// The data type.
+    pub dtype: DataType,
+    /// The shape.
+    pub tensor_shape: Option<TensorShapeProto>,
+}
+
+impl MetaGraphDef {
+    /// Find a signature.
+    #[inline]
+    pub fn signature(&self, key: &str) -> Option<&SignatureDef> {
+        self.signature_def.get(key)
+    }
+}
+
+impl Codec for MetaGraphDef {
+    fn read(&mut self, field: u32, wire: Wire, reader: &mut Reader) -> Result<()> {
+        match field {
+            1 => self.meta_info_def = Some(try!(reader.message(wire))),
+            2 => self.graph_def = Some(try!(reader.message(wire))),
+            3 => self.saver_def = try!(reader.bytes(wire)),
+            4 => try!(reader.entry(wire, &mut self.collection_def, Reader::bytes)),
+            5 => try!(reader.entry(wire, &mut self.signature_def, Reader::message)),
+            6 => self.asset_file_def.push(try!(reader.bytes(wire))),
+            _ => try!(reader.skip(wire)),
+        }
+        Ok(())
+    }
+
+    fn write(&self, writer: &mut Writer) {
+        if let Some(ref definition) = self.meta_info_def {
+            writer.message(1, definition);
+        }
+        if let Some(ref definition) = self.graph_def {
+            writer.message(2, definition);
+        }
+        writer.bytes(3, &self.saver_def);
+        for (key, value) in &self.collection_def {
+            writer.entry(4, key, |writer| writer.delimited(2, value));
+        }
+        for (key, value) in &self.signature_def {
+            writer.entry(5, key, |writer| writer.message(2, value));
+        }
+        for definition in &self.asset_file_def {
+            writer.delimited(6, definition);
+        }
+    }
+}
+
+impl Codec for MetaInfoDef {
+    fn read(&mut self, field: u32, wire: Wire, reader: &mut Reader) -> Result<()> {
+        match field {
+            1 => self.meta_graph_version = try!(reader.string(wire)),
+            2 => self.stripped_op_list = try!(reader.bytes(wire)),
+            4 => self.tags.push(try!(reader.string(wire))),
+            5 => self.tensorflow_version = try!(reader.string(wire)),
+            6 => self.tensorflow_git_version = try!(reader.string(wire)),
+            _ => try!(reader.skip(wire)),
+        }
+        Ok(())
+    }
+
+    fn write(&self, writer: &mut Writer) {
+        writer.string(1, &self.meta_graph_version);
+        writer.bytes(2, &self.stripped_op_list);
+        for tag in &self.tags {
+            writer.delimited(4, tag.as_bytes());
+        }
+        writer.string(5, &self.tensorflow_version);
+        writer.string(6, &self.tensorflow_git_version);
+    }
+}
+
+impl Codec for SignatureDef {
+    fn read(&mut self, field: u32, wire: Wire, reader: &mut Reader) -> Result<()> {
+        match field {
+            1 => try!(reader.entry(wire, &mut self.inputs, Reader::message)),
+            2 => try!(reader.entry(wire, &mut self.outputs, Reader::message)),
+            3 => self.method_name = try!(reader.string(wire)),
+            _ => try!(reader.skip(wire)),
+        }
+        Ok(())
+    }
+
+    fn write(&self, writer: &mut Writer) {
+        for (key, value) in &self.inputs {
+            writer.entry(1, key, |writer| writer.message(2, value));
+        }
+        for (key, value) in &self.outputs {
+            writer.entry(2, key, |writer| writer.message(2, value));
+        }
+        writer.string(3, &self.method_name);
+    }
+}
+
+impl Codec for TensorInfo {
+    fn read(&mut self, field: u32, wire: Wire, reader: &mut Reader) -> Result<()> {
+        match field {
+            1 => self.name = try!(reader.string(wire)),
+            2 => self.dtype = DataType::from_i32(try!(reader.int32(wire))),
+            3 => self.tensor_shape = Some(try!(reader.message(wire))),
+            _ => try!(reader.skip(wire)),
+        }
+        Ok(())
+    }
+
+    fn write(&self, writer: &mut Writer) {
+        writer.string(1, &self.name);
+        writer.int32(2, self.dtype.to_i32());
+        if let Some(ref shape) = self.tensor_shape {
+            writer.message(3, shape);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use kind::DataType;
+    use model::{MetaGraphDef, SignatureDef, TensorInfo};
+    use protobuf::Message;
+
+    #[test]
+    fn decode_encode() {
+        let mut signature = SignatureDef::default();
+        signature.method_name = "tensorflow/serving/predict".into();
+        signature.inputs.insert("x".into(), TensorInfo {
+            name: "x:0".into(),
+            dtype: DataType::Float,
+            tensor_shape: None,
+        });
+        let mut definition = MetaGraphDef::default();
+        definition.signature_def.insert("serving_default".into(), signature);
+        definition.collection_def.insert("variables".into(), vec![]);
+
+        let definition = MetaGraphDef::decode(&definition.encode()).unwrap();
+        let signature = definition.signature("serving_default").unwrap();
+        assert_eq!(signature.inputs["x"].name, "x:0");
+        assert_eq!(signature.inputs["x"].dtype, DataType::Float);
+        assert_eq!(definition.collection_def["variables"], Vec::<u8>::new());
+    }
+}
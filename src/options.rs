@@ -19,7 +19,8 @@ impl Options {
     /// Set the configuration using a protocol buffer.
     ///
     /// The scheme of the `configuration` protocol buffer is called
-    /// `ConfigProto`, and it can be found in TensorFlow’s [repository][1].
+    /// `ConfigProto`, and it can be found in TensorFlow’s [repository][1]. A
+    /// buffer can be obtained from a `Config` using `Buffer::from`.
     ///
     /// [1]: https://github.com/tensorflow/tensorflow/blob/master/tensorflow/core/protobuf/config.proto
     pub fn configure(&mut self, configuration: &Buffer) -> Result<()> {
@@ -43,6 +44,8 @@ impl Options {
     }
 }
 
+unsafe impl Send for Options {}
+
 impl Drop for Options {
     #[inline]
     fn drop(&mut self) {
@@ -0,0 +1,440 @@
+use std::collections::BTreeMap;
+
+use Result;
+use buffer::Buffer;
+
+const MAX_DEPTH: usize = 100;
+
+/// A protocol buffer.
+///
+/// The trait is sealed: it is implemented for the protocol buffers defined in
+/// this crate and cannot be implemented outside of it.
+pub trait Message: Default + Codec {
+    /// Decode a message.
+    fn decode(data: &[u8]) -> Result<Self> {
+        Reader::new(data).finish()
+    }
+
+    /// Decode a message from a buffer.
+    #[inline]
+    fn from_buffer(buffer: &Buffer) -> Result<Self> {
+        Self::decode(buffer)
+    }
+
+    /// Encode the message.
+    fn encode(&self) -> Vec<u8> {
+        let mut writer = Writer::new();
+        self.write(&mut writer);
+        writer.data
+    }
+}
+
+#[doc(hidden)]
+pub trait Codec {
+    fn read(&mut self, field: u32, wire: Wire, reader: &mut Reader) -> Result<()>;
+    fn write(&self, writer: &mut Writer);
+}
+
+impl<T> Message for T where T: Default + Codec {}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Wire {
+    Varint,
+    Fixed64,
+    Delimited,
+    Fixed32,
+}
+
+pub struct Reader<'l> {
+    data: &'l [u8],
+    position: usize,
+    depth: usize,
+}
+
+pub struct Writer {
+    data: Vec<u8>,
+}
+
+macro_rules! expect(
+    ($wire:expr, $expected:ident) => (
+        if $wire != Wire::$expected {
+            raise!("expected a protocol-buffer field of type {:?}, found {:?}",
+                   Wire::$expected, $wire);
+        }
+    );
+);
+
+impl<'l> Reader<'l> {
+    #[inline]
+    pub fn new(data: &'l [u8]) -> Self {
+        Reader { data: data, position: 0, depth: 0 }
+    }
+
+    pub fn next(&mut self) -> Result<Option<(u32, Wire)>> {
+        if self.position == self.data.len() {
+            return Ok(None);
+        }
+        let key = try!(self.varint());
+        let wire = match key & 0x7 {
+            0 => Wire::Varint,
+            1 => Wire::Fixed64,
+            2 => Wire::Delimited,
+            5 => Wire::Fixed32,
+            wire => raise!("found an unsupported protocol-buffer wire type ({})", wire),
+        };
+        Ok(Some(((key >> 3) as u32, wire)))
+    }
+
+    pub fn skip(&mut self, wire: Wire) -> Result<()> {
+        match wire {
+            Wire::Varint => { try!(self.varint()); },
+            Wire::Fixed64 => { try!(self.take(8)); },
+            Wire::Delimited => { try!(self.delimited(wire)); },
+            Wire::Fixed32 => { try!(self.take(4)); },
+        }
+        Ok(())
+    }
+
+    pub fn bool(&mut self, wire: Wire) -> Result<bool> {
+        expect!(wire, Varint);
+        Ok(try!(self.varint()) != 0)
+    }
+
+    pub fn int32(&mut self, wire: Wire) -> Result<i32> {
+        expect!(wire, Varint);
+        Ok(try!(self.varint()) as i32)
+    }
+
+    pub fn int64(&mut self, wire: Wire) -> Result<i64> {
+        expect!(wire, Varint);
+        Ok(try!(self.varint()) as i64)
+    }
+
+    pub fn uint32(&mut self, wire: Wire) -> Result<u32> {
+        expect!(wire, Varint);
+        Ok(try!(self.varint()) as u32)
+    }
+
+    pub fn uint64(&mut self, wire: Wire) -> Result<u64> {
+        expect!(wire, Varint);
+        self.varint()
+    }
+
+    pub fn float(&mut self, wire: Wire) -> Result<f32> {
+        expect!(wire, Fixed32);
+        let data = try!(self.take(4));
+        Ok(f32::from_bits(data.iter().rev().fold(0, |value, &byte| value << 8 | byte as u32)))
+    }
+
+    pub fn double(&mut self, wire: Wire) -> Result<f64> {
+        expect!(wire, Fixed64);
+        let data = try!(self.take(8));
+        Ok(f64::from_bits(data.iter().rev().fold(0, |value, &byte| value << 8 | byte as u64)))
+    }
+
+    pub fn bytes(&mut self, wire: Wire) -> Result<Vec<u8>> {
+        Ok(try!(self.delimited(wire)).to_vec())
+    }
+
+    pub fn string(&mut self, wire: Wire) -> Result<String> {
+        match String::from_utf8(try!(self.bytes(wire))) {
+            Ok(string) => Ok(string),
+            _ => raise!("found a protocol-buffer string that is not valid UTF-8"),
+        }
+    }
+
+    pub fn message<T>(&mut self, wire: Wire) -> Result<T> where T: Message {
+        if self.depth == MAX_DEPTH {
+            raise!("found a protocol buffer nested deeper than {} levels", MAX_DEPTH);
+        }
+        try!(self.nested(wire, 1)).finish()
+    }
+
+    pub fn repeated<T, F>(&mut self, wire: Wire, element: Wire, values: &mut Vec<T>,
+                          mut read: F) -> Result<()>
+        where F: FnMut(&mut Reader<'l>, Wire) -> Result<T>
+    {
+        if wire == Wire::Delimited && element != Wire::Delimited {
+            let mut reader = try!(self.nested(wire, 0));
+            while reader.position < reader.data.len() {
+                values.push(try!(read(&mut reader, element)));
+            }
+        } else {
+            values.push(try!(read(self, wire)));
+        }
+        Ok(())
+    }
+
+    pub fn entry<T, F>(&mut self, wire: Wire, entries: &mut BTreeMap<String, T>,
+                       mut read: F) -> Result<()>
+        where T: Default, F: FnMut(&mut Reader<'l>, Wire) -> Result<T>
+    {
+        let mut reader = try!(self.nested(wire, 0));
+        let (mut key, mut value) = (String::new(), T::default());
+        while let Some((field, wire)) = try!(reader.next()) {
+            match field {
+                1 => key = try!(reader.string(wire)),
+                2 => value = try!(read(&mut reader, wire)),
+                _ => try!(reader.skip(wire)),
+            }
+        }
+        entries.insert(key, value);
+        Ok(())
+    }
+
+    fn finish<T>(mut self) -> Result<T> where T: Message {
+        let mut message = T::default();
+        while let Some((field, wire)) = try!(self.next()) {
+            try!(message.read(field, wire, &mut self));
+        }
+        Ok(message)
+    }
+
+    fn nested(&mut self, wire: Wire, levels: usize) -> Result<Reader<'l>> {
+        let depth = self.depth + levels;
+        Ok(Reader { data: try!(self.delimited(wire)), position: 0, depth: depth })
+    }
+
+    fn delimited(&mut self, wire: Wire) -> Result<&'l [u8]> {
+        expect!(wire, Delimited);
+        let length = try!(self.varint());
+        if length > (self.data.len() - self.position) as u64 {
+            raise!("found a truncated protocol buffer");
+        }
+        self.take(length as usize)
+    }
+
+    fn take(&mut self, count: usize) -> Result<&'l [u8]> {
+        if count > self.data.len() - self.position {
+            raise!("found a truncated protocol buffer");
+        }
+        let data = &self.data[self.position..(self.position + count)];
+        self.position += count;
+        Ok(data)
+    }
+
+    fn varint(&mut self) -> Result<u64> {
+        let mut value = 0;
+        for i in 0..10 {
+            let byte = match self.data.get(self.position) {
+                Some(&byte) => byte,
+                _ => raise!("found a truncated protocol buffer"),
+            };
+            self.position += 1;
+            value |= ((byte & 0x7f) as u64) << (7 * i);
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        raise!("found a malformed varint in a protocol buffer")
+    }
+}
+
+impl Writer {
+    #[inline]
+    pub fn new() -> Self {
+        Writer { data: vec![] }
+    }
+
+    #[inline]
+    pub fn bool(&mut self, field: u32, value: bool) {
+        if value {
+            self.varint(field, 1);
+        }
+    }
+
+    #[inline]
+    pub fn int32(&mut self, field: u32, value: i32) {
+        if value != 0 {
+            self.varint(field, value as i64 as u64);
+        }
+    }
+
+    #[inline]
+    pub fn int64(&mut self, field: u32, value: i64) {
+        if value != 0 {
+            self.varint(field, value as u64);
+        }
+    }
+
+    #[inline]
+    pub fn uint32(&mut self, field: u32, value: u32) {
+        if value != 0 {
+            self.varint(field, value as u64);
+        }
+    }
+
+    #[inline]
+    pub fn uint64(&mut self, field: u32, value: u64) {
+        if value != 0 {
+            self.varint(field, value);
+        }
+    }
+
+    #[inline]
+    pub fn float(&mut self, field: u32, value: f32) {
+        if value != 0.0 {
+            self.fixed32(field, value.to_bits());
+        }
+    }
+
+    #[inline]
+    pub fn double(&mut self, field: u32, value: f64) {
+        if value != 0.0 {
+            self.fixed64(field, value.to_bits());
+        }
+    }
+
+    #[inline]
+    pub fn bytes(&mut self, field: u32, value: &[u8]) {
+        if !value.is_empty() {
+            self.delimited(field, value);
+        }
+    }
+
+    #[inline]
+    pub fn string(&mut self, field: u32, value: &str) {
+        self.bytes(field, value.as_bytes());
+    }
+
+    pub fn message<T>(&mut self, field: u32, value: &T) where T: Message {
+        let mut writer = Writer::new();
+        value.write(&mut writer);
+        self.delimited(field, &writer.data);
+    }
+
+    pub fn packed<T, F>(&mut self, field: u32, values: &[T], mut write: F)
+        where F: FnMut(&mut Writer, &T)
+    {
+        if values.is_empty() {
+            return;
+        }
+        let mut writer = Writer::new();
+        for value in values {
+            write(&mut writer, value);
+        }
+        self.delimited(field, &writer.data);
+    }
+
+    pub fn entry<F>(&mut self, field: u32, key: &str, write: F) where F: FnOnce(&mut Writer) {
+        let mut writer = Writer::new();
+        writer.string(1, key);
+        write(&mut writer);
+        self.delimited(field, &writer.data);
+    }
+
+    pub fn varint(&mut self, field: u32, value: u64) {
+        self.key(field, 0);
+        self.raw_varint(value);
+    }
+
+    pub fn fixed32(&mut self, field: u32, value: u32) {
+        self.key(field, 5);
+        self.raw_fixed32(value);
+    }
+
+    pub fn fixed64(&mut self, field: u32, value: u64) {
+        self.key(field, 1);
+        self.raw_fixed64(value);
+    }
+
+    pub fn delimited(&mut self, field: u32, value: &[u8]) {
+        self.key(field, 2);
+        self.raw_varint(value.len() as u64);
+        self.data.extend_from_slice(value);
+    }
+
+    #[inline]
+    pub fn raw_fixed32(&mut self, value: u32) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    #[inline]
+    pub fn raw_fixed64(&mut self, value: u64) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn raw_varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.data.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+        self.data.push(value as u8);
+    }
+
+    #[inline]
+    fn key(&mut self, field: u32, wire: u64) {
+        self.raw_varint((field as u64) << 3 | wire);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use Result;
+    use error::ErrorKind;
+    use super::{Codec, MAX_DEPTH, Message, Reader, Wire, Writer};
+
+    #[derive(Default)]
+    struct Nested(Option<Box<Nested>>);
+
+    impl Codec for Nested {
+        fn read(&mut self, field: u32, wire: Wire, reader: &mut Reader) -> Result<()> {
+            match field {
+                1 => self.0 = Some(Box::new(try!(reader.message(wire)))),
+                _ => try!(reader.skip(wire)),
+            }
+            Ok(())
+        }
+
+        fn write(&self, writer: &mut Writer) {
+            if let Some(ref nested) = self.0 {
+                writer.message(1, &**nested);
+            }
+        }
+    }
+
+    #[test]
+    fn deep() {
+        let mut message = Nested(None);
+        for _ in 0..MAX_DEPTH {
+            message = Nested(Some(Box::new(message)));
+        }
+        assert!(Nested::decode(&message.encode()).is_ok());
+        let message = Nested(Some(Box::new(message)));
+        let error = Nested::decode(&message.encode()).err().unwrap();
+        assert_eq!(error.kind(), ErrorKind::Validation);
+    }
+
+    #[test]
+    fn negative() {
+        let mut writer = Writer::new();
+        writer.int32(1, -2);
+        assert_eq!(writer.data.len(), 1 + 10);
+        let mut reader = Reader::new(&writer.data);
+        assert_eq!(reader.next().unwrap(), Some((1, Wire::Varint)));
+        assert_eq!(reader.int32(Wire::Varint).unwrap(), -2);
+        assert_eq!(reader.next().unwrap(), None);
+    }
+
+    #[test]
+    fn packed() {
+        let mut writer = Writer::new();
+        writer.packed(3, &[1i64, 300, 2], |writer, &value| writer.raw_varint(value as u64));
+        writer.varint(3, 4);
+        assert_eq!(&writer.data[..6], &[3 << 3 | 2, 4, 1, 0xac, 0x02, 2]);
+        let mut reader = Reader::new(&writer.data);
+        let mut values = vec![];
+        while let Some((field, wire)) = reader.next().unwrap() {
+            assert_eq!(field, 3);
+            reader.repeated(wire, Wire::Varint, &mut values, Reader::int64).unwrap();
+        }
+        assert_eq!(values, vec![1, 300, 2, 4]);
+    }
+
+    #[test]
+    fn truncated() {
+        let mut reader = Reader::new(&[2 << 3 | 2, 5, 1, 2]);
+        let (_, wire) = reader.next().unwrap().unwrap();
+        assert!(reader.bytes(wire).is_err());
+    }
+}
@@ -0,0 +1,69 @@
+macro_rules! implement {
+    ($($kind:ident($native:ident) => $name:expr,)*) => {$(
+        #[doc = $name]
+        #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        #[repr(C)]
+        pub struct $kind(pub $native);
+
+        impl $kind {
+            /// Convert into a floating-point number given the range of the
+            /// quantization.
+            ///
+            /// The smallest and largest representable values are mapped to
+            /// `min` and `max`, respectively.
+            pub fn dequantize(self, min: f32, max: f32) -> f32 {
+                let (lowest, highest) = ($native::MIN as f64, $native::MAX as f64);
+                let scale = (max as f64 - min as f64) / (highest - lowest);
+                (min as f64 + (self.0 as f64 - lowest) * scale) as f32
+            }
+
+            /// Convert from a floating-point number given the range of the
+            /// quantization.
+            ///
+            /// Values outside the range are clamped.
+            pub fn quantize(value: f32, min: f32, max: f32) -> Self {
+                let (lowest, highest) = ($native::MIN as f64, $native::MAX as f64);
+                let scale = (highest - lowest) / (max as f64 - min as f64);
+                let value = (lowest + (value as f64 - min as f64) * scale).round();
+                $kind(value.max(lowest).min(highest) as $native)
+            }
+        }
+
+        impl From<$native> for $kind {
+            #[inline]
+            fn from(value: $native) -> Self {
+                $kind(value)
+            }
+        }
+
+        impl From<$kind> for $native {
+            #[inline]
+            fn from(value: $kind) -> Self {
+                value.0
+            }
+        }
+    )*}
+}
+
+implement! {
+    QInt8(i8) => "A quantized 8-bit signed integer.",
+    QUInt8(u8) => "A quantized 8-bit unsigned integer.",
+    QInt16(i16) => "A quantized 16-bit signed integer.",
+    QUInt16(u16) => "A quantized 16-bit unsigned integer.",
+    QInt32(i32) => "A quantized 32-bit signed integer.",
+}
+
+#[cfg(test)]
+mod tests {
+    use quantized::{QInt8, QUInt8};
+
+    #[test]
+    fn dequantize() {
+        assert_eq!(QUInt8(0).dequantize(-1.0, 1.0), -1.0);
+        assert_eq!(QUInt8(255).dequantize(-1.0, 1.0), 1.0);
+        assert_eq!(QInt8(-128).dequantize(0.0, 6.0), 0.0);
+        assert_eq!(QInt8(127).dequantize(0.0, 6.0), 6.0);
+        assert_eq!(QUInt8::quantize(0.5, 0.0, 1.0), QUInt8(128));
+        assert_eq!(QInt8::quantize(42.0, 0.0, 6.0), QInt8(127));
+    }
+}
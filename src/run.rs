@@ -0,0 +1,151 @@
+use Result;
+use graph::GraphDef;
+use protobuf::{Codec, Reader, Wire, Writer};
+use stats::StepStats;
+
+/// Options of a run.
+///
+/// The options correspond to the `RunOptions` protocol buffer and can be
+/// passed to `Session::run` using `Buffer::from`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RunOptions {
+    /// The level of tracing.
+    pub trace_level: TraceLevel,
+    /// The timeout in milliseconds.
+    pub timeout_in_ms: i64,
+    /// The index of the thread pool for executing independent operations.
+    pub inter_op_thread_pool: i32,
+    /// The flag requesting the partition graphs to be returned.
+    pub output_partition_graphs: bool,
+}
+
+/// Metadata of a run.
+///
+/// The metadata correspond to the `RunMetadata` protocol buffer and can be
+/// obtained from the buffer passed to `Session::run` using `Message::decode`:
+///
+/// ```
+/// # use tensorflux::{Buffer, Input, Options, Output, Session, Tensor};
+/// use tensorflux::{Message, RunMetadata, RunOptions, TraceLevel};
+///
+/// # let mut session = Session::new(&Options::new().unwrap()).unwrap();
+/// # let graph = "examples/assets/multiplication.pb";
+/// # session.extend(&Buffer::load(graph).unwrap()).unwrap();
+/// # let a = Tensor::new(vec![1f32, 2.0, 3.0], &[3]).unwrap();
+/// # let b = Tensor::new(vec![4f32, 5.0, 6.0], &[3]).unwrap();
+/// # let inputs = vec![Input::new("a", a), Input::new("b", b)];
+/// # let mut outputs = vec![Output::new("c")];
+/// let mut options = RunOptions::default();
+/// options.trace_level = TraceLevel::Full;
+///
+/// let mut metadata = Buffer::new(vec![]);
+/// session.run(&inputs, &mut outputs, &[], Some(&Buffer::from(&options)),
+///             Some(&mut metadata)).unwrap();
+///
+/// let metadata = RunMetadata::decode(&metadata).unwrap();
+/// if let Some(stats) = metadata.step_stats {
+///     for device in &stats.dev_stats {
+///         for node in &device.node_stats {
+///             println!("{} {}", node.node_name, node.all_end_rel_micros);
+///         }
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RunMetadata {
+    /// The statistics of the step.
+    pub step_stats: Option<StepStats>,
+    /// The cost graph as a serialized `CostGraphDef`.
+    pub cost_graph: Vec<u8>,
+    /// The graphs executed on each device.
+    pub partition_graphs: Vec<GraphDef>,
+}
+
+/// A level of tracing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TraceLevel {
+    /// Collect no traces.
+    None,
+    /// Collect software traces.
+    Software,
+    /// Collect hardware traces.
+    Hardware,
+    /// Collect all traces.
+    Full,
+}
+
+impl Default for TraceLevel {
+    #[inline]
+    fn default() -> Self {
+        TraceLevel::None
+    }
+}
+
+impl Codec for RunOptions {
+    fn read(&mut self, field: u32, wire: Wire, reader: &mut Reader) -> Result<()> {
+        match field {
+            1 => self.trace_level = match try!(reader.int32(wire)) {
+                1 => TraceLevel::Software,
+                2 => TraceLevel::Hardware,
+                3 => TraceLevel::Full,
+                _ => TraceLevel::None,
+            },
+            2 => self.timeout_in_ms = try!(reader.int64(wire)),
+            3 => self.inter_op_thread_pool = try!(reader.int32(wire)),
+            5 => self.output_partition_graphs = try!(reader.bool(wire)),
+            _ => try!(reader.skip(wire)),
+        }
+        Ok(())
+    }
+
+    fn write(&self, writer: &mut Writer) {
+        writer.int32(1, match self.trace_level {
+            TraceLevel::None => 0,
+            TraceLevel::Software => 1,
+            TraceLevel::Hardware => 2,
+            TraceLevel::Full => 3,
+        });
+        writer.int64(2, self.timeout_in_ms);
+        writer.int32(3, self.inter_op_thread_pool);
+        writer.bool(5, self.output_partition_graphs);
+    }
+}
+
+impl Codec for RunMetadata {
+    fn read(&mut self, field: u32, wire: Wire, reader: &mut Reader) -> Result<()> {
+        match field {
+            1 => self.step_stats = Some(try!(reader.message(wire))),
+            2 => self.cost_graph = try!(reader.bytes(wire)),
+            3 => self.partition_graphs.push(try!(reader.message(wire))),
+            _ => try!(reader.skip(wire)),
+        }
+        Ok(())
+    }
+
+    fn write(&self, writer: &mut Writer) {
+        if let Some(ref stats) = self.step_stats {
+            writer.message(1, stats);
+        }
+        if !self.cost_graph.is_empty() {
+            writer.delimited(2, &self.cost_graph);
+        }
+        for graph in &self.partition_graphs {
+            writer.message(3, graph);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use protobuf::Message;
+    use run::{RunOptions, TraceLevel};
+
+    #[test]
+    fn encode() {
+        let mut options = RunOptions::default();
+        options.trace_level = TraceLevel::Full;
+        options.timeout_in_ms = 1000;
+        options.output_partition_graphs = true;
+        assert_eq!(options.encode(), vec![1 << 3, 3, 2 << 3, 0xe8, 0x07, 5 << 3, 1]);
+    }
+}
@@ -1,25 +1,115 @@
-use ffi::{TF_DataType, TF_Session, TF_Tensor};
-use libc::{c_int, size_t};
+use ffi::{TF_Buffer, TF_DataType, TF_Operation, TF_Port, TF_Session, TF_SessionWithGraph};
+use ffi::TF_Tensor;
+use libc::{c_char, c_int, size_t};
+use std::any::Any;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet};
 use std::ffi::CString;
+#[cfg(feature = "async")]
+use std::sync::Arc;
 use std::{mem, ptr};
 
-use Result;
+use {Error, Result};
 use buffer::Buffer;
+#[cfg(feature = "async")]
+use executor::{self, Task};
+use graph::Graph;
+use kind::DataType;
+use model::{SignatureDef, TensorInfo};
 use options::Options;
 use status::Status;
-use tensor::Tensor;
+use string::{cstring, split};
+use tensor::{AnyTensor, Tensor};
 use value::Value;
 
 /// A session.
+///
+/// A session can be shared between threads, and it can run several steps
+/// concurrently.
 pub struct Session {
+    raw: Raw,
     status: Status,
-    raw: *mut TF_Session,
+}
+
+enum Raw {
+    Plain(*mut TF_Session),
+    Graph(*mut TF_SessionWithGraph, Graph),
+}
+
+/// A partial run.
+///
+/// A partial run allows for feeding inputs and fetching outputs
+/// incrementally over several runs.
+///
+/// ```
+/// use tensorflux::{Buffer, Input, Options, Output, Session, Tensor};
+///
+/// macro_rules! ok(($result:expr) => ($result.unwrap()));
+///
+/// let graph = "examples/assets/multiplication.pb"; // c = a * b
+/// let mut session = ok!(Session::new(&ok!(Options::new())));
+/// ok!(session.extend(&ok!(Buffer::load(graph))));
+///
+/// let a = ok!(Tensor::new(vec![1f32, 2.0, 3.0], &[3]));
+/// let b = ok!(Tensor::new(vec![4f32, 5.0, 6.0], &[3]));
+///
+/// let inputs = vec![Input::new("a", a), Input::new("b", b)];
+/// let mut outputs = vec![Output::new("c")];
+/// let mut partial = ok!(session.partial(&inputs, &outputs, &[]));
+/// ok!(partial.run(&inputs[..1], &mut [], &[]));
+/// ok!(partial.run(&inputs[1..], &mut outputs, &[]));
+///
+/// let c = ok!(outputs[0].get::<f32>());
+/// assert_eq!(&c[..], &[1.0 * 4.0, 2.0 * 5.0, 3.0 * 6.0]);
+/// ```
+pub struct PartialRun<'l> {
+    session: &'l Session,
+    handle: *const c_char,
+}
+
+/// A builder of runs.
+///
+/// Inputs, outputs, and targets are given by name, and the outputs are looked
+/// up by name once the run is over.
+///
+/// ```
+/// use tensorflux::{Buffer, Options, Session, Tensor};
+///
+/// macro_rules! ok(($result:expr) => ($result.unwrap()));
+///
+/// let graph = "examples/assets/multiplication.pb"; // c = a * b
+/// let mut session = ok!(Session::new(&ok!(Options::new())));
+/// ok!(session.extend(&ok!(Buffer::load(graph))));
+///
+/// let a = ok!(Tensor::new(vec![1f32, 2.0, 3.0], &[3]));
+/// let b = ok!(Tensor::new(vec![4f32, 5.0, 6.0], &[3]));
+///
+/// let mut outputs = ok!(session.runner().feed("a", &a).feed("b", &b).fetch("c").run());
+///
+/// let c = ok!(outputs.get::<f32>("c"));
+/// assert_eq!(&c[..], &[1.0 * 4.0, 2.0 * 5.0, 3.0 * 6.0]);
+/// ```
+pub struct RunBuilder<'l> {
+    session: &'l Session,
+    inputs: Vec<Input<'l>>,
+    outputs: Vec<Output>,
+    targets: Vec<Target>,
+    options: Option<&'l Buffer>,
+    metadata: Option<&'l mut Buffer>,
+}
+
+/// Outputs of a run.
+pub struct Outputs {
+    outputs: Vec<Output>,
 }
 
 /// An input.
-pub struct Input {
+///
+/// An input either owns its tensor or borrows it for the lifetime `'l`, in
+/// which case the same tensor can be fed to several inputs or sessions.
+pub struct Input<'l> {
     name: CString,
-    tensor: Option<Box<Flexor>>,
+    tensor: Option<Feed<'l>>,
 }
 
 /// An output.
@@ -33,17 +123,55 @@ pub struct Target {
     name: CString,
 }
 
-trait Flexor {
-    fn copy_raw(&self) -> Result<*mut TF_Tensor>;
+enum Feed<'l> {
+    Owned(Box<Flexor>),
+    Borrowed(&'l Flexor),
+}
+
+trait Flexor: Send + Sync {
+    fn as_any(&self) -> &Any;
+    fn into_any(self: Box<Self>) -> Box<Any>;
+    fn to_raw(&self) -> Result<*mut TF_Tensor>;
     fn kind(&self) -> TF_DataType;
+    fn dimensions(&self) -> Vec<usize>;
+}
+
+struct Ports {
+    inputs: Vec<TF_Port>,
+    outputs: Vec<TF_Port>,
+    targets: Vec<*const TF_Operation>,
 }
 
+macro_rules! names(
+    ($items:expr) => (names!($items, |item| item.name.as_ptr()));
+    ($items:expr, $map:expr) => ($items.iter().map($map).collect::<Vec<_>>());
+);
+
 impl Session {
     /// Create a session.
     pub fn new(options: &Options) -> Result<Self> {
         let status = try!(Status::new());
         let raw = nonnull!(ffi!(TF_NewSession(options.as_raw(), status.as_raw())), &status);
-        Ok(Session { status: status, raw: raw })
+        Ok(Session { raw: Raw::Plain(raw), status: status })
+    }
+
+    /// Create a session for a graph.
+    ///
+    /// The graph can be accessed via `graph` and extended further.
+    pub fn from_graph(graph: Graph, options: &Options) -> Result<Self> {
+        let status = try!(Status::new());
+        let raw = nonnull!(ffi!(TF_NewSessionWithGraph(graph.as_raw(), options.as_raw(),
+                                                       status.as_raw())), &status);
+        Ok(Session { raw: Raw::Graph(raw, graph), status: status })
+    }
+
+    /// Return the graph if the session was created from one.
+    #[inline]
+    pub fn graph(&self) -> Option<&Graph> {
+        match self.raw {
+            Raw::Graph(_, ref graph) => Some(graph),
+            _ => None,
+        }
     }
 
     /// Extend the graph using a protocol buffer.
@@ -51,14 +179,23 @@ impl Session {
     /// The schema of the `definition` protocol buffer is called `GraphDef`, and
     /// it can be found in TensorFlow’s [repository][1]. An example of creating
     /// a graph definition is given in the [main description][2] of this
-    /// package.
+    /// package. A definition can be inspected prior to extending the graph
+    /// using the types in the [`graph`][3] module.
+    ///
+    /// The graph of a session created from a graph cannot be extended this way.
     ///
     /// [1]: https://github.com/tensorflow/tensorflow/blob/master/tensorflow/core/framework/graph.proto
     /// [2]: index.html#example
+    /// [3]: graph/index.html
     pub fn extend(&mut self, definition: &Buffer) -> Result<()> {
+        let raw = match self.raw {
+            Raw::Plain(raw) => raw,
+            _ => raise!("the graph of the session cannot be extended this way"),
+        };
+        let status = try!(Status::new());
         let definition = definition.as_ref();
-        ok!(ffi!(TF_ExtendGraph(self.raw, definition.as_ptr() as *const _,
-                                definition.len() as size_t, self.status.as_raw())), &self.status);
+        ok!(ffi!(TF_ExtendGraph(raw, definition.as_ptr() as *const _,
+                                definition.len() as size_t, status.as_raw())), &status);
         Ok(())
     }
 
@@ -66,50 +203,14 @@ impl Session {
     ///
     /// The schemas of the `options` and `metadata` protocol buffers are called
     /// `RunOptions` and `RunMetadata`, respectively, and they can be found in
-    /// TensorFlow’s [repository][1].
+    /// TensorFlow’s [repository][1]. The corresponding types are `RunOptions`
+    /// and `RunMetadata`; the `metadata` buffer should be empty prior to the
+    /// call.
     ///
     /// [1]: https://github.com/tensorflow/tensorflow/blob/master/tensorflow/core/protobuf/config.proto
-    pub fn run(&mut self, inputs: &[Input], outputs: &mut [Output], targets: &[Target],
+    pub fn run(&self, inputs: &[Input], outputs: &mut [Output], targets: &[Target],
                options: Option<&Buffer>, metadata: Option<&mut Buffer>) -> Result<()> {
 
-        let ni = inputs.len();
-        let mut input_names = vec![ptr::null(); ni];
-        let mut input_tensors = vec![ptr::null_mut(); ni];
-
-        macro_rules! cleanup(() => ({
-            for tensor in input_tensors.drain(..) {
-                ffi!(TF_DeleteTensor(tensor));
-            }
-        }));
-
-        for i in 0..ni {
-            input_names[i] = inputs[i].name.as_ptr();
-            input_tensors[i] = match inputs[i].tensor.as_ref().map(|tensor| tensor.copy_raw()) {
-                Some(Ok(tensor)) => tensor,
-                Some(Err(error)) => {
-                    cleanup!();
-                    return Err(error);
-                },
-                _ => {
-                    cleanup!();
-                    raise!("some of the inputs have not been set");
-                },
-            };
-        }
-
-        let no = outputs.len();
-        let mut output_names = vec![ptr::null(); no];
-        let mut output_tensors = vec![ptr::null_mut(); no];
-        for i in 0..no {
-            output_names[i] = outputs[i].name.as_ptr();
-        }
-
-        let nt = targets.len();
-        let mut target_names = vec![ptr::null(); nt];
-        for i in 0..nt {
-            target_names[i] = targets[i].name.as_ptr();
-        }
-
         let options_buffer = if let Some(buffer) = options {
             buffer.as_raw()
         } else {
@@ -122,14 +223,7 @@ impl Session {
             ptr::null_mut()
         };
 
-        ok!(ffi!(TF_Run(self.raw, options_buffer, input_names.as_mut_ptr(),
-                        input_tensors.as_mut_ptr(), ni as c_int, output_names.as_mut_ptr(),
-                        output_tensors.as_mut_ptr(), no as c_int, target_names.as_mut_ptr(),
-                        nt as c_int, metadata_buffer, self.status.as_raw())), &self.status);
-
-        for i in 0..no {
-            outputs[i].set(output_tensors[i]);
-        }
+        try!(self.execute(None, inputs, outputs, targets, options_buffer, metadata_buffer));
 
         if let Some(buffer) = metadata {
             unsafe { buffer.reset() };
@@ -137,39 +231,366 @@ impl Session {
 
         Ok(())
     }
+
+    /// Run the graph asynchronously.
+    ///
+    /// The run is executed on a pool of worker threads, and the returned
+    /// future resolves to the outputs along with the metadata buffer, which is
+    /// filled in if given. Dropping the future cancels the run if it has not
+    /// started yet and discards the outputs otherwise.
+    #[cfg(feature = "async")]
+    pub fn run_async(self: Arc<Self>, inputs: Vec<Input<'static>>, mut outputs: Vec<Output>,
+                     targets: Vec<Target>, options: Option<Buffer>, mut metadata: Option<Buffer>)
+                     -> Task<(Vec<Output>, Option<Buffer>)> {
+
+        executor::spawn(move || {
+            try!(self.run(&inputs, &mut outputs, &targets, options.as_ref(), metadata.as_mut()));
+            Ok((outputs, metadata))
+        })
+    }
+
+    /// Start building a run.
+    #[inline]
+    pub fn runner<'l>(&'l self) -> RunBuilder<'l> {
+        RunBuilder {
+            session: self,
+            inputs: vec![],
+            outputs: vec![],
+            targets: vec![],
+            options: None,
+            metadata: None,
+        }
+    }
+
+    /// Run the graph according to a signature.
+    ///
+    /// The names of the inputs should be the logical names given in the
+    /// signature, such as `x` rather than `x:0`, and each input of the
+    /// signature should be fed. The data types and dimensions of the tensors
+    /// are validated against the signature. All the outputs of the signature
+    /// are fetched, their data types are validated against the signature,
+    /// and they are returned indexed by their logical names.
+    ///
+    /// ```no_run
+    /// use tensorflux::{Buffer, Input, Message, Options, Session, Tensor};
+    /// use tensorflux::model::MetaGraphDef;
+    ///
+    /// let definition = MetaGraphDef::decode(&Buffer::load("model.meta").unwrap()).unwrap();
+    /// let mut session = Session::new(&Options::new().unwrap()).unwrap();
+    /// session.extend(&Buffer::from(definition.graph_def.as_ref().unwrap())).unwrap();
+    /// let signature = definition.signature("serving_default").unwrap();
+    ///
+    /// let x = Tensor::new(vec![1f32, 2.0, 3.0], &[1, 3]).unwrap();
+    /// let mut outputs = session.run_signature(signature, vec![Input::new("x", x)]).unwrap();
+    /// let y = outputs.get_mut("y").unwrap().get::<f32>().unwrap();
+    /// ```
+    pub fn run_signature(&self, signature: &SignatureDef, inputs: Vec<Input>)
+                         -> Result<BTreeMap<String, Output>> {
+
+        let mut fed = BTreeSet::new();
+        let mut feeds = Vec::with_capacity(inputs.len());
+        for mut input in inputs {
+            let key = name(&input.name).into_owned();
+            let info = match signature.inputs.get(&key) {
+                Some(info) => info,
+                _ => raise!("input `{}` is not part of the signature", key),
+            };
+            if let Some(tensor) = input.flexor() {
+                let data_type = DataType::from_raw(tensor.kind());
+                try!(validate(&key, info, data_type, &tensor.dimensions()));
+            }
+            feeds.push(Input { name: try!(cstring(&info.name)), tensor: input.tensor.take() });
+            fed.insert(key);
+        }
+        for key in signature.inputs.keys() {
+            if !fed.contains(key) {
+                raise!("input `{}` of the signature has not been fed", key);
+            }
+        }
+
+        let mut outputs = Vec::with_capacity(signature.outputs.len());
+        for info in signature.outputs.values() {
+            outputs.push(Output { name: try!(cstring(&info.name)), tensor: None });
+        }
+        try!(self.run(&feeds, &mut outputs, &[], None, None));
+        for ((key, info), output) in signature.outputs.iter().zip(&outputs) {
+            try!(check(key, info, output));
+        }
+        Ok(signature.outputs.keys().cloned().zip(outputs).collect())
+    }
+
+    /// Set up a partial run.
+    ///
+    /// All the inputs, outputs, and targets that will be used in the
+    /// subsequent runs of the partial run should be declared; the tensors of
+    /// the inputs are ignored.
+    ///
+    /// The C API of the supported TensorFlow version provides no means of
+    /// deleting the handle of a partial run, and, therefore, a small amount of
+    /// memory is leaked per set-up.
+    pub fn partial<'l>(&'l self, inputs: &[Input], outputs: &[Output], targets: &[Target])
+                       -> Result<PartialRun<'l>> {
+
+        let status = try!(Status::new());
+        let mut handle = ptr::null();
+        match self.raw {
+            Raw::Plain(raw) => {
+                let mut input_names = names!(inputs);
+                let mut output_names = names!(outputs);
+                let mut target_names = names!(targets);
+                ffi!(TF_PRunSetup(raw, input_names.as_mut_ptr(), inputs.len() as c_int,
+                                  output_names.as_mut_ptr(), outputs.len() as c_int,
+                                  target_names.as_mut_ptr(), targets.len() as c_int, &mut handle,
+                                  status.as_raw()));
+            },
+            Raw::Graph(raw, ref graph) => {
+                let ports = try!(Ports::new(graph, inputs, outputs, targets));
+                ffi!(TF_SessionPRunSetup(raw, ports.inputs.as_ptr(), inputs.len() as c_int,
+                                         ports.outputs.as_ptr(), outputs.len() as c_int,
+                                         ports.targets.as_ptr(), targets.len() as c_int,
+                                         &mut handle, status.as_raw()));
+            },
+        }
+        if let Some(error) = Error::from_status(&status) {
+            return Err(error.within(describe(inputs, outputs, targets)));
+        }
+        Ok(PartialRun { session: self, handle: nonnull!(handle) })
+    }
+
+    fn execute(&self, handle: Option<*const c_char>, inputs: &[Input], outputs: &mut [Output],
+               targets: &[Target], options: *mut TF_Buffer, metadata: *mut TF_Buffer)
+               -> Result<()> {
+
+        let ports = match self.raw {
+            Raw::Graph(_, ref graph) => Some(try!(Ports::new(graph, inputs, outputs, targets))),
+            _ => None,
+        };
+
+        let status = try!(Status::new());
+        let (mut input_names, mut input_tensors) = try!(prepare(inputs));
+        let mut output_tensors = vec![ptr::null_mut(); outputs.len()];
+
+        match (&self.raw, ports) {
+            (&Raw::Plain(raw), _) => {
+                let mut output_names = names!(outputs);
+                let mut target_names = names!(targets);
+                match handle {
+                    Some(handle) => ffi!(TF_PRun(raw, handle, input_names.as_mut_ptr(),
+                                                 input_tensors.as_mut_ptr(), inputs.len() as c_int,
+                                                 output_names.as_mut_ptr(),
+                                                 output_tensors.as_mut_ptr(),
+                                                 outputs.len() as c_int,
+                                                 target_names.as_mut_ptr(),
+                                                 targets.len() as c_int, status.as_raw())),
+                    _ => ffi!(TF_Run(raw, options, input_names.as_mut_ptr(),
+                                     input_tensors.as_mut_ptr(), inputs.len() as c_int,
+                                     output_names.as_mut_ptr(), output_tensors.as_mut_ptr(),
+                                     outputs.len() as c_int, target_names.as_mut_ptr(),
+                                     targets.len() as c_int, metadata, status.as_raw())),
+                }
+            },
+            (&Raw::Graph(raw, _), Some(ports)) => {
+                match handle {
+                    Some(handle) => ffi!(TF_SessionPRun(raw, handle, ports.inputs.as_ptr(),
+                                                        input_tensors.as_ptr(),
+                                                        inputs.len() as c_int,
+                                                        ports.outputs.as_ptr(),
+                                                        output_tensors.as_mut_ptr(),
+                                                        outputs.len() as c_int,
+                                                        ports.targets.as_ptr(),
+                                                        targets.len() as c_int,
+                                                        status.as_raw())),
+                    _ => ffi!(TF_SessionRun(raw, options, ports.inputs.as_ptr(),
+                                            input_tensors.as_ptr(), inputs.len() as c_int,
+                                            ports.outputs.as_ptr(), output_tensors.as_mut_ptr(),
+                                            outputs.len() as c_int, ports.targets.as_ptr(),
+                                            targets.len() as c_int, metadata,
+                                            status.as_raw())),
+                }
+            },
+            _ => unreachable!(),
+        }
+        if let Some(error) = Error::from_status(&status) {
+            return Err(error.within(describe(inputs, outputs, targets)));
+        }
+
+        for (output, tensor) in outputs.iter_mut().zip(output_tensors) {
+            output.set(tensor);
+        }
+
+        Ok(())
+    }
 }
 
 impl Drop for Session {
+    // The status allocated at creation is reused so that closing and deleting
+    // cannot be skipped due to a failed allocation.
     #[inline]
     fn drop(&mut self) {
-        ffi!(TF_CloseSession(self.raw, self.status.as_raw()));
-        ffi!(TF_DeleteSession(self.raw, self.status.as_raw()));
+        let status = &self.status;
+        match self.raw {
+            Raw::Plain(raw) => {
+                ffi!(TF_CloseSession(raw, status.as_raw()));
+                ffi!(TF_DeleteSession(raw, status.as_raw()));
+            },
+            Raw::Graph(raw, _) => {
+                ffi!(TF_CloseSessionWithGraph(raw, status.as_raw()));
+                ffi!(TF_DeleteSessionWithGraph(raw, status.as_raw()));
+            },
+        }
+    }
+}
+
+unsafe impl Send for Session {}
+
+unsafe impl Sync for Session {}
+
+impl<'l> PartialRun<'l> {
+    /// Run the graph partially.
+    ///
+    /// The inputs, outputs, and targets should be among those declared when
+    /// the partial run was set up. Each input should be fed exactly once
+    /// across all the runs, and each output should be fetched at most once.
+    pub fn run(&mut self, inputs: &[Input], outputs: &mut [Output], targets: &[Target])
+               -> Result<()> {
+
+        self.session.execute(Some(self.handle), inputs, outputs, targets, ptr::null_mut(),
+                             ptr::null_mut())
+    }
+}
+
+impl<'l> RunBuilder<'l> {
+    /// Feed a tensor.
+    ///
+    /// The tensor is borrowed until the builder is run.
+    #[inline]
+    pub fn feed<T, U>(mut self, name: T, tensor: &'l Tensor<U>) -> Self
+        where T: Into<String>, U: Value
+    {
+        self.inputs.push(Input::borrow(name, tensor));
+        self
+    }
+
+    /// Fetch an output.
+    ///
+    /// The name is in the form `node:index` or `node`, which refers to the
+    /// first output.
+    #[inline]
+    pub fn fetch<T>(mut self, name: T) -> Self where T: Into<String> {
+        self.outputs.push(Output::new(name));
+        self
+    }
+
+    /// Add a target.
+    #[inline]
+    pub fn target<T>(mut self, name: T) -> Self where T: Into<String> {
+        self.targets.push(Target::new(name));
+        self
+    }
+
+    /// Set the options.
+    ///
+    /// The buffer should contain a `RunOptions` protocol buffer.
+    #[inline]
+    pub fn options(mut self, options: &'l Buffer) -> Self {
+        self.options = Some(options);
+        self
+    }
+
+    /// Collect metadata into a buffer.
+    ///
+    /// The buffer will contain a `RunMetadata` protocol buffer.
+    #[inline]
+    pub fn metadata(mut self, metadata: &'l mut Buffer) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+
+    /// Run the graph.
+    pub fn run(mut self) -> Result<Outputs> {
+        try!(self.session.run(&self.inputs, &mut self.outputs, &self.targets, self.options,
+                              self.metadata));
+        Ok(Outputs { outputs: self.outputs })
     }
 }
 
-impl Input {
-    /// Create an input.
+impl<'l> Input<'l> {
+    /// Create an input owning a tensor.
     #[inline]
     pub fn new<T, U>(name: T, tensor: Tensor<U>) -> Self where T: Into<String>, U: Value {
-        Input { name: into_cstring!(name), tensor: Some(Box::new(tensor)) }
+        Input { name: into_cstring!(name), tensor: Some(Feed::Owned(Box::new(tensor))) }
+    }
+
+    /// Create an input borrowing a tensor.
+    #[inline]
+    pub fn borrow<T, U>(name: T, tensor: &'l Tensor<U>) -> Self where T: Into<String>, U: Value {
+        Input { name: into_cstring!(name), tensor: Some(Feed::Borrowed(tensor)) }
+    }
+
+    /// Return the name.
+    #[inline]
+    pub fn name<'m>(&'m self) -> Cow<'m, str> {
+        name(&self.name)
+    }
+
+    /// Return the data type of the tensor if it has been set.
+    #[inline]
+    pub fn data_type(&self) -> Option<DataType> {
+        self.flexor().map(|tensor| DataType::from_raw(tensor.kind()))
+    }
+
+    /// Return the dimensions of the tensor if it has been set.
+    #[inline]
+    pub fn dimensions(&self) -> Option<Vec<usize>> {
+        self.flexor().map(|tensor| tensor.dimensions())
     }
 
     /// Extract the tensor.
+    ///
+    /// Borrowed tensors cannot be extracted.
     pub fn get<T>(&mut self) -> Result<Tensor<T>> where T: Value {
-        if self.tensor.is_none() {
-            raise!("the tensor has not been set");
+        let (kind, dimensions, matches) = match self.tensor {
+            Some(Feed::Owned(ref tensor)) => {
+                (tensor.kind(), tensor.dimensions(), tensor.as_any().is::<Tensor<T>>())
+            },
+            Some(Feed::Borrowed(_)) => raise!("input `{}` is borrowed", name(&self.name)),
+            _ => raise!("input `{}` has not been set", name(&self.name)),
+        };
+        if kind != T::kind() {
+            raise!("input `{}` has data type {} and dimensions {:?}, but {} is requested",
+                   name(&self.name), DataType::from_raw(kind), dimensions, T::data_type());
+        }
+        if !matches {
+            raise!("input `{}` has data type {}, but its elements are of another type",
+                   name(&self.name), DataType::from_raw(kind));
         }
-        if self.tensor.as_ref().unwrap().kind() != T::kind() {
-            raise!("the data types do not match");
+        match self.tensor.take() {
+            Some(Feed::Owned(tensor)) => match tensor.into_any().downcast() {
+                Ok(tensor) => Ok(*tensor),
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
         }
-        let tensor = self.tensor.take().unwrap();
-        Ok(*unsafe { Box::from_raw(Box::into_raw(tensor) as *mut _) })
     }
 
     /// Assign a tensor.
     #[inline]
     pub fn set<T>(&mut self, tensor: Tensor<T>) where T: Value {
-        self.tensor = Some(Box::new(tensor));
+        self.tensor = Some(Feed::Owned(Box::new(tensor)));
+    }
+
+    /// Assign a borrowed tensor.
+    #[inline]
+    pub fn set_borrowed<T>(&mut self, tensor: &'l Tensor<T>) where T: Value {
+        self.tensor = Some(Feed::Borrowed(tensor));
+    }
+
+    fn flexor(&self) -> Option<&Flexor> {
+        match self.tensor {
+            Some(Feed::Owned(ref tensor)) => Some(&**tensor),
+            Some(Feed::Borrowed(tensor)) => Some(tensor),
+            _ => None,
+        }
     }
 }
 
@@ -181,11 +602,40 @@ impl Output {
     }
 
     /// Extract the tensor.
+    ///
+    /// The data stay in the memory allocated by TensorFlow. Converting the
+    /// tensor into an array copies them once; `view` does not copy.
     pub fn get<T>(&mut self) -> Result<Tensor<T>> where T: Value {
         match self.tensor.take() {
-            Some(tensor) => Tensor::from_raw(tensor),
-            _ => raise!("the tensor has not been set"),
+            Some(tensor) => Tensor::from_raw(tensor).map_err(|error| {
+                error.within(format!("output `{}`", name(&self.name)))
+            }),
+            _ => raise!("output `{}` has not been set", name(&self.name)),
+        }
+    }
+
+    /// Extract the tensor without specifying its data type.
+    pub fn take_any(&mut self) -> Result<AnyTensor> {
+        match self.tensor.take() {
+            Some(tensor) => Ok(AnyTensor::from_raw(tensor)),
+            _ => raise!("output `{}` has not been set", name(&self.name)),
+        }
+    }
+
+    /// Extract the data point of a tensor of rank zero.
+    pub fn get_scalar<T>(&mut self) -> Result<T> where T: Value {
+        let tensor = try!(self.get::<T>());
+        if tensor.rank() != 0 {
+            raise!("output `{}` should be of rank zero, but it has dimensions {:?}",
+                   name(&self.name), tensor.dimensions());
         }
+        Ok(tensor[0].clone())
+    }
+
+    /// Return the data type of the tensor if it has been set.
+    #[inline]
+    pub fn data_type(&self) -> Option<DataType> {
+        self.tensor.map(|tensor| DataType::from_raw(ffi!(TF_TensorType(tensor))))
     }
 
     #[inline]
@@ -196,6 +646,38 @@ impl Output {
     }
 }
 
+unsafe impl Send for Output {}
+
+impl Outputs {
+    /// Extract the tensor of an output.
+    ///
+    /// The name can be any name referring to a fetched output; for instance,
+    /// `c` and `c:0` are interchangeable.
+    pub fn get<T>(&mut self, name: &str) -> Result<Tensor<T>> where T: Value {
+        try!(self.find(name)).get()
+    }
+
+    /// Extract the tensor of an output without specifying its data type.
+    pub fn take_any(&mut self, name: &str) -> Result<AnyTensor> {
+        try!(self.find(name)).take_any()
+    }
+
+    /// Extract the data point of an output of rank zero.
+    pub fn get_scalar<T>(&mut self, name: &str) -> Result<T> where T: Value {
+        try!(self.find(name)).get_scalar()
+    }
+
+    fn find(&mut self, name: &str) -> Result<&mut Output> {
+        let key = split(name);
+        for output in &mut self.outputs {
+            if split(&self::name(&output.name)) == key {
+                return Ok(output);
+            }
+        }
+        raise!("output `{}` has not been fetched", name)
+    }
+}
+
 impl Drop for Output {
     #[inline]
     fn drop(&mut self) {
@@ -213,21 +695,136 @@ impl Target {
     }
 }
 
+fn prepare(inputs: &[Input]) -> Result<(Vec<*const c_char>, Vec<*mut TF_Tensor>)> {
+    let mut tensors = Vec::with_capacity(inputs.len());
+
+    macro_rules! cleanup(() => ({
+        for tensor in tensors.drain(..) {
+            ffi!(TF_DeleteTensor(tensor));
+        }
+    }));
+
+    for input in inputs {
+        tensors.push(match input.flexor() {
+            Some(tensor) => match tensor.to_raw() {
+                Ok(raw) => raw,
+                Err(error) => {
+                    cleanup!();
+                    let context = format!("input `{}` of data type {} and dimensions {:?}",
+                                          name(&input.name), DataType::from_raw(tensor.kind()),
+                                          tensor.dimensions());
+                    return Err(error.within(context));
+                },
+            },
+            _ => {
+                cleanup!();
+                raise!("input `{}` has not been set", name(&input.name));
+            },
+        });
+    }
+
+    Ok((names!(inputs), tensors))
+}
+
+impl Ports {
+    fn new(graph: &Graph, inputs: &[Input], outputs: &[Output], targets: &[Target])
+           -> Result<Self> {
+
+        let mut ports = Ports {
+            inputs: Vec::with_capacity(inputs.len()),
+            outputs: Vec::with_capacity(outputs.len()),
+            targets: Vec::with_capacity(targets.len()),
+        };
+        for input in inputs {
+            ports.inputs.push(try!(graph.port(&name(&input.name))));
+        }
+        for output in outputs {
+            ports.outputs.push(try!(graph.port(&name(&output.name))));
+        }
+        for target in targets {
+            ports.targets.push(try!(graph.operation(&name(&target.name))) as *const _);
+        }
+        Ok(ports)
+    }
+}
+
+fn validate(key: &str, info: &TensorInfo, data_type: DataType, dimensions: &[usize])
+            -> Result<()> {
+
+    if info.dtype != data_type {
+        raise!("input `{}` (`{}`) should have data type {}, but it has data type {}",
+               key, info.name, info.dtype, data_type);
+    }
+    if let Some(ref shape) = info.tensor_shape {
+        if !shape.matches(dimensions) {
+            let expected = shape.dim.iter().map(|dimension| dimension.size).collect::<Vec<_>>();
+            raise!("input `{}` (`{}`) should have dimensions {:?}, but it has dimensions {:?}",
+                   key, info.name, expected, dimensions);
+        }
+    }
+    Ok(())
+}
+
+fn check(key: &str, info: &TensorInfo, output: &Output) -> Result<()> {
+    match output.data_type() {
+        Some(data_type) if data_type != info.dtype => {
+            raise!("output `{}` (`{}`) should have data type {}, but it has data type {}",
+                   key, info.name, info.dtype, data_type);
+        },
+        _ => Ok(()),
+    }
+}
+
+fn describe(inputs: &[Input], outputs: &[Output], targets: &[Target]) -> String {
+    macro_rules! list(($items:expr) => ({
+        $items.iter().map(|item| format!("`{}`", name(&item.name))).collect::<Vec<_>>().join(", ")
+    }));
+    format!("failed to run with inputs [{}], outputs [{}], and targets [{}]",
+            list!(inputs), list!(outputs), list!(targets))
+}
+
+#[inline]
+fn name<'l>(name: &'l CString) -> Cow<'l, str> {
+    name.to_string_lossy()
+}
+
 impl<T> Flexor for Tensor<T> where T: Value {
     #[inline]
-    fn copy_raw(&self) -> Result<*mut TF_Tensor> {
-        self.copy_raw()
+    fn as_any(&self) -> &Any {
+        self
+    }
+
+    #[inline]
+    fn into_any(self: Box<Self>) -> Box<Any> {
+        self
+    }
+
+    #[inline]
+    fn to_raw(&self) -> Result<*mut TF_Tensor> {
+        self.to_raw()
     }
 
     #[inline]
     fn kind(&self) -> TF_DataType {
         T::kind()
     }
+
+    #[inline]
+    fn dimensions(&self) -> Vec<usize> {
+        self.dimensions()
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use session::Input;
+    use buffer::Buffer;
+    use graph::{Dimension, Graph, TensorShapeProto};
+    use kind::DataType;
+    use model::TensorInfo;
+    use options::Options;
+    use session::{Input, Output, Outputs, Session, check, validate};
+    use std::sync::Arc;
+    use std::thread;
     use tensor::Tensor;
 
     #[test]
@@ -237,4 +834,151 @@ mod tests {
         let a = a.get::<f64>().unwrap();
         assert_eq!(&a[..], &[42.0, 69.0]);
     }
+
+    #[test]
+    fn input_get_mismatch() {
+        let a = Tensor::new(vec![42.0, 69.0], &[2, 1]).unwrap();
+        let mut a = Input::new("a", a);
+        let error = a.get::<f32>().err().unwrap();
+        assert_eq!(error.to_string(), "input `a` has data type float64 and dimensions [2, 1], \
+                                       but float32 is requested");
+        assert!(a.get::<f64>().is_ok());
+        let error = a.get::<f64>().err().unwrap();
+        assert_eq!(error.to_string(), "input `a` has not been set");
+
+        let b = Tensor::new(vec![String::from("foo")], &[1]).unwrap();
+        let mut b = Input::new("b", b);
+        let error = b.get::<Vec<u8>>().err().unwrap();
+        assert_eq!(error.to_string(), "input `b` has data type string, but its elements are \
+                                       of another type");
+        assert_eq!(b.data_type(), Some(DataType::String));
+        assert_eq!(b.dimensions(), Some(vec![1]));
+        assert!(b.get::<String>().is_ok());
+    }
+
+    #[test]
+    fn input_borrow() {
+        let mut a = Tensor::new(vec![42.0, 69.0], &[2]).unwrap();
+        {
+            let mut input = Input::borrow("a", &a);
+            let error = input.get::<f64>().err().unwrap();
+            assert_eq!(error.to_string(), "input `a` is borrowed");
+            assert_eq!(input.flexor().unwrap().dimensions(), vec![2]);
+        }
+        a[0] = 0.0;
+        let input = Input::borrow("a", &a);
+        assert_eq!(input.flexor().unwrap().dimensions(), vec![2]);
+    }
+
+    #[test]
+    fn outputs_find() {
+        let mut outputs = Outputs { outputs: vec![Output::new("c"), Output::new("d:1")] };
+        assert!(outputs.find("c:0").is_ok());
+        assert!(outputs.find("d:1").is_ok());
+        let error = outputs.find("d").err().unwrap();
+        assert_eq!(error.to_string(), "output `d` has not been fetched");
+        let error = outputs.get::<f32>("c").err().unwrap();
+        assert_eq!(error.to_string(), "output `c` has not been set");
+    }
+
+    #[test]
+    fn validate_signature() {
+        let mut info = TensorInfo::default();
+        info.name = "x:0".into();
+        info.dtype = DataType::Float;
+        assert!(validate("x", &info, DataType::Float, &[2, 3]).is_ok());
+        let error = validate("x", &info, DataType::Double, &[2, 3]).err().unwrap();
+        assert_eq!(error.to_string(), "input `x` (`x:0`) should have data type float32, \
+                                       but it has data type float64");
+
+        let mut shape = TensorShapeProto::default();
+        shape.dim.push(Dimension { size: -1, name: String::new() });
+        shape.dim.push(Dimension { size: 3, name: String::new() });
+        info.tensor_shape = Some(shape);
+        assert!(validate("x", &info, DataType::Float, &[2, 3]).is_ok());
+        let error = validate("x", &info, DataType::Float, &[3]).err().unwrap();
+        assert_eq!(error.to_string(), "input `x` (`x:0`) should have dimensions [-1, 3], \
+                                       but it has dimensions [3]");
+    }
+
+    #[test]
+    fn check_signature() {
+        let mut info = TensorInfo::default();
+        info.name = "y:0".into();
+        info.dtype = DataType::Double;
+        let mut output = Output::new("y:0");
+        assert!(check("y", &info, &output).is_ok());
+        output.set(Tensor::new(vec![1f32, 2.0], &[2]).unwrap().to_raw().unwrap());
+        let error = check("y", &info, &output).err().unwrap();
+        assert_eq!(error.to_string(), "output `y` (`y:0`) should have data type float64, \
+                                       but it has data type float32");
+        info.dtype = DataType::Float;
+        assert!(check("y", &info, &output).is_ok());
+    }
+
+    #[test]
+    fn run_concurrently() {
+        let mut session = Session::new(&Options::new().unwrap()).unwrap();
+        let graph = Buffer::load("examples/assets/multiplication.pb").unwrap();
+        session.extend(&graph).unwrap();
+        let session = Arc::new(session);
+        let threads = (0..8).map(|i| {
+            let session = session.clone();
+            thread::spawn(move || {
+                for j in 0..16 {
+                    let a = Tensor::new(vec![i as f32, j as f32], &[2]).unwrap();
+                    let b = Tensor::new(vec![2f32, 3.0], &[2]).unwrap();
+                    let inputs = vec![Input::new("a", a), Input::new("b", b)];
+                    let mut outputs = vec![Output::new("c")];
+                    session.run(&inputs, &mut outputs, &[], None, None).unwrap();
+                    let c = outputs[0].get::<f32>().unwrap();
+                    assert_eq!(&c[..], &[2.0 * i as f32, 3.0 * j as f32]);
+                }
+            })
+        }).collect::<Vec<_>>();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn run_async() {
+        use executor::block_on;
+
+        let mut session = Session::new(&Options::new().unwrap()).unwrap();
+        let graph = Buffer::load("examples/assets/multiplication.pb").unwrap();
+        session.extend(&graph).unwrap();
+        let session = Arc::new(session);
+        let tasks = (0..8).map(|i| {
+            let a = Tensor::new(vec![i as f32, 1.0], &[2]).unwrap();
+            let b = Tensor::new(vec![2f32, 3.0], &[2]).unwrap();
+            let inputs = vec![Input::new("a", a), Input::new("b", b)];
+            session.clone().run_async(inputs, vec![Output::new("c")], vec![], None,
+                                      Some(Buffer::new(vec![])))
+        }).collect::<Vec<_>>();
+        for (i, task) in tasks.into_iter().enumerate() {
+            let (mut outputs, metadata) = block_on(task).unwrap();
+            assert!(metadata.is_some());
+            let c = outputs[0].get::<f32>().unwrap();
+            assert_eq!(&c[..], &[2.0 * i as f32, 3.0]);
+        }
+    }
+
+    #[test]
+    fn send_sync() {
+        fn send<T: Send>() {}
+        fn sync<T: Sync>() {}
+        send::<Buffer>();
+        send::<Graph>();
+        send::<Input>();
+        send::<Options>();
+        send::<Output>();
+        send::<Session>();
+        send::<Tensor<String>>();
+        sync::<Graph>();
+        sync::<Input>();
+        sync::<Session>();
+        sync::<Tensor<f32>>();
+    }
 }
@@ -0,0 +1,369 @@
+//! Statistics of runs.
+//!
+//! The types in this module mirror the protocol buffers that TensorFlow uses
+//! to report the execution of a step, and they can be found in TensorFlow’s
+//! [repository][1]. The statistics are collected when a session is run with
+//! tracing enabled via `RunOptions`, and they can be exported in the [trace
+//! event format][2] of Chrome, which can be viewed using `chrome://tracing` or
+//! [Perfetto][3].
+//!
+//! [1]: https://github.com/tensorflow/tensorflow/blob/master/tensorflow/core/framework/step_stats.proto
+//! [2]: https://docs.google.com/document/d/1CvAClvFfyA5R-PhYUmn5OOQtYMH4h6I0nSsKchNAySU
+//! [3]: https://ui.perfetto.dev
+
+use std::fmt::Write as FmtWrite;
+use std::io::Write;
+
+use Result;
+use graph::TensorShapeProto;
+use kind::DataType;
+use protobuf::{Codec, Reader, Wire, Writer};
+
+/// Statistics of a step.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StepStats {
+    /// The statistics of each device.
+    pub dev_stats: Vec<DeviceStepStats>,
+}
+
+/// Statistics of a step on a device.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DeviceStepStats {
+    /// The device.
+    pub device: String,
+    /// The statistics of each node.
+    pub node_stats: Vec<NodeExecStats>,
+}
+
+/// Statistics of the execution of a node.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NodeExecStats {
+    /// The name of the node.
+    pub node_name: String,
+    /// The start time in microseconds since the epoch.
+    pub all_start_micros: i64,
+    /// The start time of the operation in microseconds relative to the start.
+    pub op_start_rel_micros: i64,
+    /// The end time of the operation in microseconds relative to the start.
+    pub op_end_rel_micros: i64,
+    /// The end time in microseconds relative to the start.
+    pub all_end_rel_micros: i64,
+    /// The memory used by each allocator.
+    pub memory: Vec<AllocatorMemoryUsed>,
+    /// The outputs.
+    pub output: Vec<NodeOutput>,
+    /// The label to show in timelines.
+    pub timeline_label: String,
+    /// The time of scheduling in microseconds since the epoch.
+    pub scheduled_micros: i64,
+    /// The identifier of the thread.
+    pub thread_id: u32,
+    /// The tensors referenced by the node.
+    pub referenced_tensor: Vec<AllocationDescription>,
+}
+
+/// Memory used by an allocator.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AllocatorMemoryUsed {
+    /// The name of the allocator.
+    pub allocator_name: String,
+    /// The total number of bytes.
+    pub total_bytes: i64,
+    /// The peak number of bytes.
+    pub peak_bytes: i64,
+    /// The number of bytes that are still in use.
+    pub live_bytes: i64,
+}
+
+/// An output of a node.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NodeOutput {
+    /// The index of the output.
+    pub slot: i32,
+    /// The description of the tensor.
+    pub tensor_description: Option<TensorDescription>,
+}
+
+/// A description of a tensor.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TensorDescription {
+    /// The data type.
+    pub dtype: DataType,
+    /// The shape.
+    pub shape: Option<TensorShapeProto>,
+    /// The description of the allocation.
+    pub allocation_description: Option<AllocationDescription>,
+}
+
+/// A description of an allocation.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AllocationDescription {
+    /// The number of bytes requested.
+    pub requested_bytes: i64,
+    /// The number of bytes allocated.
+    pub allocated_bytes: i64,
+    /// The name of the allocator.
+    pub allocator_name: String,
+    /// The identifier of the allocation.
+    pub allocation_id: i64,
+    /// The flag indicating that the allocation has a single reference.
+    pub has_single_reference: bool,
+    /// The address of the allocation.
+    pub ptr: u64,
+}
+
+impl StepStats {
+    /// Export the statistics in the trace event format of Chrome.
+    ///
+    /// Each device is represented by a process, each thread of a device by a
+    /// thread, and each node by an event spanning its execution. The memory
+    /// used by each allocator is represented by a counter.
+    pub fn to_chrome_trace(&self) -> String {
+        let mut events = vec![];
+        for (pid, device) in self.dev_stats.iter().enumerate() {
+            events.push(format!(concat!(r#"{{"name":"process_name","ph":"M","pid":{},"#,
+                                        r#""args":{{"name":{}}}}}"#),
+                                pid, quote(&device.device)));
+            for node in &device.node_stats {
+                let operation = operation(node);
+                events.push(format!(concat!(r#"{{"name":{},"cat":"Op","ph":"X","pid":{},"#,
+                                            r#""tid":{},"ts":{},"dur":{},"#,
+                                            r#""args":{{"name":{},"op":{}}}}}"#),
+                                    quote(operation), pid, node.thread_id,
+                                    node.all_start_micros, node.all_end_rel_micros,
+                                    quote(&node.node_name), quote(operation)));
+                for memory in &node.memory {
+                    events.push(format!(concat!(r#"{{"name":{},"ph":"C","pid":{},"ts":{},"#,
+                                                r#""args":{{"total_bytes":{},"#,
+                                                r#""peak_bytes":{}}}}}"#),
+                                        quote(&memory.allocator_name), pid,
+                                        node.all_start_micros, memory.total_bytes,
+                                        memory.peak_bytes));
+                }
+            }
+        }
+        format!(r#"{{"traceEvents":[{}]}}"#, events.join(","))
+    }
+
+    /// Write the statistics in the trace event format of Chrome.
+    pub fn write_chrome_trace<T>(&self, mut writer: T) -> Result<()> where T: Write {
+        ok!(writer.write_all(self.to_chrome_trace().as_bytes()));
+        Ok(())
+    }
+}
+
+impl Codec for StepStats {
+    fn read(&mut self, field: u32, wire: Wire, reader: &mut Reader) -> Result<()> {
+        match field {
+            1 => self.dev_stats.push(try!(reader.message(wire))),
+            _ => try!(reader.skip(wire)),
+        }
+        Ok(())
+    }
+
+    fn write(&self, writer: &mut Writer) {
+        for stats in &self.dev_stats {
+            writer.message(1, stats);
+        }
+    }
+}
+
+impl Codec for DeviceStepStats {
+    fn read(&mut self, field: u32, wire: Wire, reader: &mut Reader) -> Result<()> {
+        match field {
+            1 => self.device = try!(reader.string(wire)),
+            2 => self.node_stats.push(try!(reader.message(wire))),
+            _ => try!(reader.skip(wire)),
+        }
+        Ok(())
+    }
+
+    fn write(&self, writer: &mut Writer) {
+        writer.string(1, &self.device);
+        for stats in &self.node_stats {
+            writer.message(2, stats);
+        }
+    }
+}
+
+impl Codec for NodeExecStats {
+    fn read(&mut self, field: u32, wire: Wire, reader: &mut Reader) -> Result<()> {
+        match field {
+            1 => self.node_name = try!(reader.string(wire)),
+            2 => self.all_start_micros = try!(reader.int64(wire)),
+            3 => self.op_start_rel_micros = try!(reader.int64(wire)),
+            4 => self.op_end_rel_micros = try!(reader.int64(wire)),
+            5 => self.all_end_rel_micros = try!(reader.int64(wire)),
+            6 => self.memory.push(try!(reader.message(wire))),
+            7 => self.output.push(try!(reader.message(wire))),
+            8 => self.timeline_label = try!(reader.string(wire)),
+            9 => self.scheduled_micros = try!(reader.int64(wire)),
+            10 => self.thread_id = try!(reader.uint32(wire)),
+            11 => self.referenced_tensor.push(try!(reader.message(wire))),
+            _ => try!(reader.skip(wire)),
+        }
+        Ok(())
+    }
+
+    fn write(&self, writer: &mut Writer) {
+        writer.string(1, &self.node_name);
+        writer.int64(2, self.all_start_micros);
+        writer.int64(3, self.op_start_rel_micros);
+        writer.int64(4, self.op_end_rel_micros);
+        writer.int64(5, self.all_end_rel_micros);
+        for memory in &self.memory {
+            writer.message(6, memory);
+        }
+        for output in &self.output {
+            writer.message(7, output);
+        }
+        writer.string(8, &self.timeline_label);
+        writer.int64(9, self.scheduled_micros);
+        writer.uint32(10, self.thread_id);
+        for tensor in &self.referenced_tensor {
+            writer.message(11, tensor);
+        }
+    }
+}
+
+impl Codec for AllocatorMemoryUsed {
+    fn read(&mut self, field: u32, wire: Wire, reader: &mut Reader) -> Result<()> {
+        match field {
+            1 => self.allocator_name = try!(reader.string(wire)),
+            2 => self.total_bytes = try!(reader.int64(wire)),
+            3 => self.peak_bytes = try!(reader.int64(wire)),
+            4 => self.live_bytes = try!(reader.int64(wire)),
+            _ => try!(reader.skip(wire)),
+        }
+        Ok(())
+    }
+
+    fn write(&self, writer: &mut Writer) {
+        writer.string(1, &self.allocator_name);
+        writer.int64(2, self.total_bytes);
+        writer.int64(3, self.peak_bytes);
+        writer.int64(4, self.live_bytes);
+    }
+}
+
+impl Codec for NodeOutput {
+    fn read(&mut self, field: u32, wire: Wire, reader: &mut Reader) -> Result<()> {
+        match field {
+            1 => self.slot = try!(reader.int32(wire)),
+            3 => self.tensor_description = Some(try!(reader.message(wire))),
+            _ => try!(reader.skip(wire)),
+        }
+        Ok(())
+    }
+
+    fn write(&self, writer: &mut Writer) {
+        writer.int32(1, self.slot);
+        if let Some(ref description) = self.tensor_description {
+            writer.message(3, description);
+        }
+    }
+}
+
+impl Codec for TensorDescription {
+    fn read(&mut self, field: u32, wire: Wire, reader: &mut Reader) -> Result<()> {
+        match field {
+            1 => self.dtype = DataType::from_i32(try!(reader.int32(wire))),
+            2 => self.shape = Some(try!(reader.message(wire))),
+            4 => self.allocation_description = Some(try!(reader.message(wire))),
+            _ => try!(reader.skip(wire)),
+        }
+        Ok(())
+    }
+
+    fn write(&self, writer: &mut Writer) {
+        writer.int32(1, self.dtype.to_i32());
+        if let Some(ref shape) = self.shape {
+            writer.message(2, shape);
+        }
+        if let Some(ref description) = self.allocation_description {
+            writer.message(4, description);
+        }
+    }
+}
+
+impl Codec for AllocationDescription {
+    fn read(&mut self, field: u32, wire: Wire, reader: &mut Reader) -> Result<()> {
+        match field {
+            1 => self.requested_bytes = try!(reader.int64(wire)),
+            2 => self.allocated_bytes = try!(reader.int64(wire)),
+            3 => self.allocator_name = try!(reader.string(wire)),
+            4 => self.allocation_id = try!(reader.int64(wire)),
+            5 => self.has_single_reference = try!(reader.bool(wire)),
+            6 => self.ptr = try!(reader.uint64(wire)),
+            _ => try!(reader.skip(wire)),
+        }
+        Ok(())
+    }
+
+    fn write(&self, writer: &mut Writer) {
+        writer.int64(1, self.requested_bytes);
+        writer.int64(2, self.allocated_bytes);
+        writer.string(3, &self.allocator_name);
+        writer.int64(4, self.allocation_id);
+        writer.bool(5, self.has_single_reference);
+        writer.uint64(6, self.ptr);
+    }
+}
+
+fn operation(node: &NodeExecStats) -> &str {
+    let label = &node.timeline_label;
+    match (label.find(" = "), label.find('(')) {
+        (Some(i), Some(j)) if i + 3 <= j => &label[(i + 3)..j],
+        _ => &node.node_name,
+    }
+}
+
+fn quote(value: &str) -> String {
+    let mut result = String::with_capacity(value.len() + 2);
+    result.push('"');
+    for character in value.chars() {
+        match character {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            character if (character as u32) < 0x20 => {
+                let _ = write!(result, "\\u{:04x}", character as u32);
+            },
+            character => result.push(character),
+        }
+    }
+    result.push('"');
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use stats::{AllocatorMemoryUsed, DeviceStepStats, NodeExecStats, StepStats};
+
+    #[test]
+    fn to_chrome_trace() {
+        let mut node = NodeExecStats::default();
+        node.node_name = "c".into();
+        node.timeline_label = "c = Mul(a, b)".into();
+        node.all_start_micros = 100;
+        node.all_end_rel_micros = 5;
+        node.thread_id = 7;
+        node.memory.push(AllocatorMemoryUsed {
+            allocator_name: "cpu".into(),
+            total_bytes: 12,
+            peak_bytes: 12,
+            live_bytes: 0,
+        });
+        let mut device = DeviceStepStats::default();
+        device.device = "/job:localhost/cpu:0 \"main\"".into();
+        device.node_stats.push(node);
+        let stats = StepStats { dev_stats: vec![device] };
+        assert_eq!(stats.to_chrome_trace(), concat!(
+            r#"{"traceEvents":["#,
+            r#"{"name":"process_name","ph":"M","pid":0,"args":{"name":"/job:localhost/cpu:0 \"main\""}},"#,
+            r#"{"name":"Mul","cat":"Op","ph":"X","pid":0,"tid":7,"ts":100,"dur":5,"args":{"name":"c","op":"Mul"}},"#,
+            r#"{"name":"cpu","ph":"C","pid":0,"ts":100,"args":{"total_bytes":12,"peak_bytes":12}}"#,
+            r#"]}"#,
+        ));
+    }
+}
@@ -0,0 +1,145 @@
+//! Encoding of string tensors.
+//!
+//! The data of a tensor of type `TF_STRING` start with a table of 64-bit
+//! offsets, one per element, followed by the elements themselves. Each offset
+//! is relative to the end of the table, and each element is prefixed with its
+//! length encoded as a varint.
+
+use libc::c_int;
+use std::ffi::CString;
+
+use Result;
+
+const OFFSET_SIZE: usize = 8;
+
+/// Encode a sequence of byte strings.
+pub fn encode<T>(elements: &[T]) -> Vec<u8> where T: AsRef<[u8]> {
+    let n = elements.len();
+    let size = elements.iter().fold(n * OFFSET_SIZE, |sum, element| {
+        let length = element.as_ref().len();
+        sum + varint_size(length as u64) + length
+    });
+    let mut data = Vec::with_capacity(size);
+    let mut offset = 0u64;
+    for element in elements {
+        let length = element.as_ref().len();
+        data.extend_from_slice(&offset.to_le_bytes());
+        offset += (varint_size(length as u64) + length) as u64;
+    }
+    for element in elements {
+        let element = element.as_ref();
+        write_varint(&mut data, element.len() as u64);
+        data.extend_from_slice(element);
+    }
+    data
+}
+
+/// Decode a sequence of byte strings.
+pub fn decode(data: &[u8], count: usize) -> Result<Vec<&[u8]>> {
+    let start = match count.checked_mul(OFFSET_SIZE) {
+        Some(start) if start <= data.len() => start,
+        _ => raise!("the string data are too short for {} element(s)", count),
+    };
+    let (table, body) = (&data[..start], &data[start..]);
+    let mut elements = Vec::with_capacity(count);
+    for i in 0..count {
+        let mut bytes = [0u8; OFFSET_SIZE];
+        bytes.copy_from_slice(&table[i * OFFSET_SIZE..(i + 1) * OFFSET_SIZE]);
+        let offset = u64::from_le_bytes(bytes) as usize;
+        if offset > body.len() {
+            raise!("the offset of string element {} is out of bounds", i);
+        }
+        let (length, consumed) = match read_varint(&body[offset..]) {
+            Some(result) => result,
+            _ => raise!("the length of string element {} is malformed", i),
+        };
+        let start = offset + consumed;
+        let end = match start.checked_add(length as usize) {
+            Some(end) if end <= body.len() => end,
+            _ => raise!("string element {} is out of bounds", i),
+        };
+        elements.push(&body[start..end]);
+    }
+    Ok(elements)
+}
+
+/// Convert a string into one suitable for the C API.
+pub fn cstring(value: &str) -> Result<CString> {
+    match CString::new(value) {
+        Ok(value) => Ok(value),
+        _ => raise!("the string `{}` should not contain null characters", value),
+    }
+}
+
+/// Split a name in the form `node:index` or `node`, which refers to the
+/// first output.
+pub fn split(name: &str) -> (&str, c_int) {
+    match name.rfind(':') {
+        Some(i) => match name[(i + 1)..].parse::<c_int>() {
+            Ok(index) => (&name[..i], index),
+            _ => (name, 0),
+        },
+        _ => (name, 0),
+    }
+}
+
+fn read_varint(data: &[u8]) -> Option<(u64, usize)> {
+    let mut value = 0u64;
+    for (i, &byte) in data.iter().enumerate().take(10) {
+        value |= ((byte & 0x7f) as u64) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Some((value, i + 1));
+        }
+    }
+    None
+}
+
+fn varint_size(mut value: u64) -> usize {
+    let mut size = 1;
+    while value >= 0x80 {
+        value >>= 7;
+        size += 1;
+    }
+    size
+}
+
+fn write_varint(data: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        data.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    data.push(value as u8);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, encode, split};
+
+    #[test]
+    fn decode_malformed() {
+        assert!(decode(&[0, 0, 0], 1).is_err());
+        assert!(decode(&[0, 0, 0, 0, 0, 0, 0, 0, 5, 42], 1).is_err());
+        assert!(decode(&[9, 0, 0, 0, 0, 0, 0, 0, 0], 1).is_err());
+    }
+
+    #[test]
+    fn encode_decode() {
+        let elements = vec![String::from("foo"), String::new(), "x".repeat(200)];
+        let data = encode(&elements);
+        assert_eq!(data.len(), 3 * 8 + (1 + 3) + (1 + 0) + (2 + 200));
+        assert_eq!(&data[..8], &[0, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(&data[8..16], &[4, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(&data[16..24], &[5, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(&data[24..28], b"\x03foo");
+        let decoded = decode(&data, 3).unwrap();
+        assert_eq!(decoded, vec![&b"foo"[..], &b""[..], elements[2].as_bytes()]);
+    }
+
+    #[test]
+    fn split_name() {
+        assert_eq!(split("a"), ("a", 0));
+        assert_eq!(split("a:1"), ("a", 1));
+        assert_eq!(split("model/a:42"), ("model/a", 42));
+        assert_eq!(split("a:b"), ("a:b", 0));
+    }
+}
@@ -1,31 +1,56 @@
-use ffi::TF_Tensor;
-use libc::{c_int, c_void, int64_t, size_t};
-use std::ptr;
+use ffi::{TF_DataType, TF_Tensor};
+use libc::{c_int, int64_t, size_t};
+use std::ops::{DerefMut, Index, IndexMut, Range, RangeFrom, RangeFull, RangeTo};
+use std::sync::Mutex;
+use std::{mem, ptr, slice};
 
-use Result;
-use memory::Memory;
+#[cfg(feature = "ndarray")]
+use ndarray::{ArrayD, ArrayViewD, ArrayViewMutD, IxDyn};
+
+use {Error, Result};
+use kind::DataType;
+use memory::{self, Memory};
 use value::Value;
 
 /// A tensor.
+///
+/// Tensors of strings can be created using either `String` or `Vec<u8>` as
+/// the element type; the latter is suitable for data that are not valid
+/// UTF-8, such as serialized protocol buffers.
+///
+/// The data of a tensor are shared with TensorFlow without copying. In
+/// particular, the data of a tensor obtained from an output stay in the memory
+/// allocated by TensorFlow until the tensor is modified, which results in a
+/// copy. Tensors of strings are encoded once and reused until modified.
+///
+/// TensorFlow takes the ownership of the handles of input tensors and deletes
+/// them after each run, and, therefore, a fresh handle is created per run. The
+/// handle refers to the data of the tensor or to its cached encoding, and
+/// creating it involves no copying.
 pub struct Tensor<T> {
     dimensions: Vec<int64_t>,
     memory: Memory<T>,
-    raw: *mut TF_Tensor,
+    encoded: Mutex<Option<Memory<u8>>>,
 }
 
 impl<T> Tensor<T> where T: Value {
     /// Create a tensor.
+    ///
+    /// The number of data points should be equal to the product of the
+    /// dimensions.
     pub fn new(data: Vec<T>, dimensions: &[usize]) -> Result<Self> {
         let (given, needed) = (data.len(), dimensions.iter().fold(1, |p, &d| p * d));
-        if needed > given {
-            raise!("there should be at least {} data point(s)", needed);
+        if needed != given {
+            raise!("there should be {} data point(s), but {} given", needed, given);
         }
         let dimensions = dimensions.iter().map(|&d| d as int64_t).collect::<Vec<_>>();
-        let memory = Memory::new(data);
-        let raw = nonnull!(ffi!(TF_NewTensor(T::kind(), dimensions.as_ptr(),
-                                dimensions.len() as c_int, memory.as_ptr() as *mut _,
-                                needed as size_t, Some(noop), ptr::null_mut())));
-        Ok(Tensor { dimensions: dimensions, memory: memory, raw: raw })
+        Ok(Tensor::from_parts(dimensions, Memory::new(data)))
+    }
+
+    /// Create a tensor of rank zero holding a single data point.
+    #[inline]
+    pub fn scalar(value: T) -> Self {
+        Tensor::from_parts(vec![], Memory::new(vec![value]))
     }
 
     /// Return the dimensions.
@@ -33,36 +58,558 @@ impl<T> Tensor<T> where T: Value {
         self.dimensions.iter().map(|&d| d as usize).collect()
     }
 
+    /// Return the number of dimensions.
+    #[inline]
+    pub fn rank(&self) -> usize {
+        self.dimensions.len()
+    }
+
+    /// Return the strides.
+    ///
+    /// A stride is the number of data points between two consecutive indices
+    /// along a dimension.
+    pub fn strides(&self) -> Vec<usize> {
+        let mut strides = vec![1; self.dimensions.len()];
+        for i in (1..strides.len()).rev() {
+            strides[i - 1] = strides[i] * self.dimensions[i] as usize;
+        }
+        strides
+    }
+
+    /// Change the dimensions.
+    ///
+    /// The product of the dimensions should stay the same.
+    pub fn reshape(&mut self, dimensions: &[usize]) -> Result<()> {
+        let (given, needed) = (self.count(), dimensions.iter().fold(1, |p, &d| p * d));
+        if needed != given {
+            raise!("cannot reshape a tensor of {} data point(s) into {:?}", given, dimensions);
+        }
+        self.dimensions = dimensions.iter().map(|&d| d as int64_t).collect();
+        Ok(())
+    }
+
+    /// Iterate over the sub-tensors along the first dimension.
+    ///
+    /// Each sub-tensor is given as a slice of its data.
+    pub fn rows<'l>(&'l self) -> Rows<'l, T> {
+        let (count, size) = match self.dimensions.split_first() {
+            Some((&count, rest)) => (count as usize, rest.iter().fold(1, |p, &d| p * d as usize)),
+            _ => (0, 0),
+        };
+        Rows { data: &self.memory[..(count * size)], size: size, position: 0, count: count }
+    }
+
+    /// Extract the sub-tensors in a range along the first dimension.
+    pub fn slice(&self, range: Range<usize>) -> Result<Self> {
+        let mut dimensions = self.dimensions();
+        if dimensions.is_empty() {
+            raise!("cannot slice a tensor of rank zero");
+        }
+        if range.start > range.end || range.end > dimensions[0] {
+            raise!("the range {:?} is out of bounds for {} sub-tensor(s)", range, dimensions[0]);
+        }
+        let size = dimensions[1..].iter().fold(1, |p, &d| p * d);
+        dimensions[0] = range.end - range.start;
+        Tensor::new(self.memory[(range.start * size)..(range.end * size)].to_vec(), &dimensions)
+    }
+
+    /// Join tensors along the first dimension.
+    ///
+    /// The tensors should have the same dimensions except for the first one.
+    pub fn concatenate(tensors: &[Tensor<T>]) -> Result<Self> {
+        let mut dimensions = match tensors.first() {
+            Some(tensor) => tensor.dimensions(),
+            _ => raise!("there should be at least one tensor to concatenate"),
+        };
+        if dimensions.is_empty() {
+            raise!("cannot concatenate tensors of rank zero");
+        }
+        let mut data = Vec::new();
+        dimensions[0] = 0;
+        for tensor in tensors {
+            let given = tensor.dimensions();
+            if given.is_empty() || given[1..] != dimensions[1..] {
+                raise!("cannot concatenate a tensor with dimensions {:?} to one with {:?}",
+                       given, dimensions);
+            }
+            dimensions[0] += given[0];
+            data.extend_from_slice(&tensor.memory[..tensor.count()]);
+        }
+        Tensor::new(data, &dimensions)
+    }
+
+    /// Convert the data into 32-bit floating-point numbers.
+    pub fn to_f32(&self) -> Tensor<f32> where T: Copy + Into<f32> {
+        let data = self.memory.iter().map(|&value| value.into()).collect();
+        Tensor::from_parts(self.dimensions.clone(), Memory::new(data))
+    }
+
+    /// Create a TensorFlow tensor sharing the data.
+    ///
+    /// The ownership of the result is transferred to the caller. A new handle
+    /// is created per call, but the data are shared without copying.
     #[doc(hidden)]
-    pub fn copy_raw(&self) -> Result<*mut TF_Tensor> {
-        Ok(nonnull!(ffi!(TF_NewTensor(T::kind(), self.dimensions.as_ptr(),
-                         self.dimensions.len() as c_int, self.as_ptr() as *mut _,
-                         self.len() as size_t, Some(noop), ptr::null_mut()))))
+    pub fn to_raw(&self) -> Result<*mut TF_Tensor> {
+        let count = self.count();
+        let raw = match self.encode(count) {
+            Some(memory) => share(T::kind(), &self.dimensions, &memory, memory.len()),
+            _ => share(T::kind(), &self.dimensions, &self.memory, count),
+        };
+        Ok(nonnull!(raw))
     }
 
+    /// Take the ownership of a TensorFlow tensor.
     #[doc(hidden)]
     pub fn from_raw(raw: *mut TF_Tensor) -> Result<Self> {
-        if ffi!(TF_TensorType(raw)) != T::kind() {
-            raise!("the data types do not match");
+        match Tensor::adopt(raw) {
+            Ok(tensor) => Ok(tensor),
+            Err(error) => {
+                ffi!(TF_DeleteTensor(raw));
+                Err(error)
+            },
         }
-        let pointer = nonnull!(ffi!(TF_TensorData(raw))) as *mut _;
+    }
+
+    /// Return a view of the data shaped according to the dimensions.
+    ///
+    /// The view borrows the data without copying, including the data owned
+    /// by TensorFlow.
+    #[cfg(feature = "ndarray")]
+    pub fn view<'l>(&'l self) -> ArrayViewD<'l, T> {
+        let data = &self.memory[..self.count()];
+        debug_assert_eq!(data.len(), self.dimensions().iter().product::<usize>());
+        unsafe { ArrayViewD::from_shape_ptr(IxDyn(&self.dimensions()), data.as_ptr()) }
+    }
+
+    /// Return a mutable view of the data shaped according to the dimensions.
+    #[cfg(feature = "ndarray")]
+    pub fn view_mut<'l>(&'l mut self) -> ArrayViewMutD<'l, T> {
+        let (dimensions, count) = (self.dimensions(), self.count());
+        let data = &mut self[..count];
+        debug_assert_eq!(data.len(), dimensions.iter().product::<usize>());
+        unsafe { ArrayViewMutD::from_shape_ptr(IxDyn(&dimensions), data.as_mut_ptr()) }
+    }
+
+    fn encode(&self, count: usize) -> Option<Memory<u8>> {
+        let mut encoded = recover!(self.encoded.lock());
+        if encoded.is_none() {
+            *encoded = T::encode(&self.memory[..count]).map(Memory::new);
+        }
+        encoded.clone()
+    }
+
+    // The ownership of the TensorFlow tensor is taken only in case of success.
+    fn adopt(raw: *mut TF_Tensor) -> Result<Self> {
         let dimensions = (0..ffi!(TF_NumDims(raw))).map(|i| ffi!(TF_Dim(raw, i)))
                                                    .collect::<Vec<_>>();
-        let length = if dimensions.is_empty() { 0 } else {
-            dimensions.iter().fold(1, |p, &d| p * d as usize)
+        let kind = ffi!(TF_TensorType(raw));
+        if kind != T::kind() {
+            raise!("expected data type {}, but the tensor has data type {} and dimensions {:?}",
+                   T::data_type(), DataType::from_raw(kind), dimensions);
+        }
+        let length = dimensions.iter().fold(1, |p, &d| p * d as usize);
+        if length == 0 {
+            ffi!(TF_DeleteTensor(raw));
+            return Ok(Tensor::from_parts(dimensions, Memory::new(vec![])));
+        }
+        let pointer = ffi!(TF_TensorData(raw)) as *mut T;
+        if pointer.is_null() {
+            return Err(Error::from_null());
+        }
+        let size = ffi!(TF_TensorByteSize(raw)) as usize;
+        let data = unsafe { slice::from_raw_parts(pointer as *const u8, size) };
+        let memory = match T::decode(data, length) {
+            Some(result) => {
+                let data = try!(result);
+                ffi!(TF_DeleteTensor(raw));
+                Memory::new(data)
+            },
+            _ => unsafe { Memory::from_tensor(raw, pointer, length) },
         };
-        let memory = unsafe { Memory::from_raw_parts(pointer, length) };
-        Ok(Tensor { dimensions: dimensions, memory: memory, raw: raw })
+        Ok(Tensor::from_parts(dimensions, memory))
     }
 }
 
+impl<T> Tensor<T> {
+    fn offset(&self, index: &[usize]) -> usize {
+        assert!(index.len() == self.dimensions.len(),
+                "the index should have {} component(s)", self.dimensions.len());
+        let mut offset = 0;
+        for (&i, &d) in index.iter().zip(self.dimensions.iter()) {
+            assert!(i < d as usize, "the index {:?} is out of bounds", index);
+            offset = offset * d as usize + i;
+        }
+        offset
+    }
+
+    #[inline]
+    fn count(&self) -> usize {
+        self.dimensions.iter().fold(1, |p, &d| p * d as usize)
+    }
+
+    #[inline]
+    fn from_parts(dimensions: Vec<int64_t>, memory: Memory<T>) -> Self {
+        Tensor { dimensions: dimensions, memory: memory, encoded: Mutex::new(None) }
+    }
+}
+
+/// A tensor whose data type is known only at runtime.
+///
+/// The data type and dimensions can be inspected prior to converting the
+/// tensor into a typed one using `downcast`.
+pub struct AnyTensor {
+    raw: *mut TF_Tensor,
+}
+
+/// An iterator over the sub-tensors of a tensor along the first dimension.
+pub struct Rows<'l, T: 'l> {
+    data: &'l [T],
+    size: usize,
+    position: usize,
+    count: usize,
+}
+
 memory!(Tensor<T>);
 
-impl<T> Drop for Tensor<T> {
+impl AnyTensor {
+    /// Return the data type.
+    #[inline]
+    pub fn data_type(&self) -> DataType {
+        DataType::from_raw(ffi!(TF_TensorType(self.raw)))
+    }
+
+    /// Return the dimensions.
+    pub fn dimensions(&self) -> Vec<usize> {
+        (0..ffi!(TF_NumDims(self.raw))).map(|i| ffi!(TF_Dim(self.raw, i)) as usize).collect()
+    }
+
+    /// Check if the data type corresponds to a particular type.
+    #[inline]
+    pub fn is<T>(&self) -> bool where T: Value {
+        ffi!(TF_TensorType(self.raw)) == T::kind()
+    }
+
+    /// Convert into a tensor of a particular type.
+    ///
+    /// The tensor is given back unchanged if the data types do not match or
+    /// if the data cannot be decoded.
+    pub fn downcast<T>(self) -> ::std::result::Result<Tensor<T>, AnyTensor> where T: Value {
+        if !self.is::<T>() {
+            return Err(self);
+        }
+        match Tensor::adopt(self.raw) {
+            Ok(tensor) => {
+                mem::forget(self);
+                Ok(tensor)
+            },
+            _ => Err(self),
+        }
+    }
+
+    /// Take the ownership of a TensorFlow tensor.
+    #[doc(hidden)]
+    #[inline]
+    pub fn from_raw(raw: *mut TF_Tensor) -> Self {
+        AnyTensor { raw: raw }
+    }
+}
+
+unsafe impl Send for AnyTensor {}
+
+impl Drop for AnyTensor {
     #[inline]
     fn drop(&mut self) {
         ffi!(TF_DeleteTensor(self.raw));
     }
 }
 
-unsafe extern "C" fn noop(_: *mut c_void, _: size_t, _: *mut c_void) {}
+macro_rules! index {
+    ($($count:expr),*) => ($(
+        impl<T> Index<[usize; $count]> for Tensor<T> {
+            type Output = T;
+
+            #[inline]
+            fn index(&self, index: [usize; $count]) -> &T {
+                &self.memory[self.offset(&index)]
+            }
+        }
+
+        impl<T> IndexMut<[usize; $count]> for Tensor<T> where T: Clone {
+            #[inline]
+            fn index_mut(&mut self, index: [usize; $count]) -> &mut T {
+                let offset = self.offset(&index);
+                &mut self.deref_mut()[offset]
+            }
+        }
+    )*);
+}
+
+index!(0, 1, 2, 3, 4, 5, 6, 7, 8);
+
+macro_rules! slice {
+    ($($index:ty => $output:ty),*) => ($(
+        impl<T> Index<$index> for Tensor<T> {
+            type Output = $output;
+
+            #[inline]
+            fn index(&self, index: $index) -> &$output {
+                &self.memory[index]
+            }
+        }
+
+        impl<T> IndexMut<$index> for Tensor<T> where T: Clone {
+            #[inline]
+            fn index_mut(&mut self, index: $index) -> &mut $output {
+                &mut self.deref_mut()[index]
+            }
+        }
+    )*);
+}
+
+slice!(usize => T, Range<usize> => [T], RangeFrom<usize> => [T], RangeFull => [T],
+       RangeTo<usize> => [T]);
+
+impl<T> Clone for Tensor<T> {
+    /// Clone the tensor.
+    ///
+    /// The data are shared until either tensor is modified.
+    #[inline]
+    fn clone(&self) -> Self {
+        Tensor::from_parts(self.dimensions.clone(), self.memory.clone())
+    }
+}
+
+impl<T> DerefMut for Tensor<T> where T: Clone {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut [T] {
+        match self.encoded.get_mut() {
+            Ok(encoded) => *encoded = None,
+            Err(poisoned) => *poisoned.into_inner() = None,
+        }
+        &mut self.memory
+    }
+}
+
+impl<'l, T> Iterator for Rows<'l, T> {
+    type Item = &'l [T];
+
+    fn next(&mut self) -> Option<&'l [T]> {
+        if self.position == self.count {
+            return None;
+        }
+        let start = self.position * self.size;
+        self.position += 1;
+        Some(&self.data[start..(start + self.size)])
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let count = self.count - self.position;
+        (count, Some(count))
+    }
+}
+
+impl<'l, T> ExactSizeIterator for Rows<'l, T> {
+}
+
+#[cfg(feature = "ndarray")]
+impl<T> From<ArrayD<T>> for Tensor<T> where T: Value {
+    /// Create a tensor from an array without copying the data if the array is
+    /// in the standard layout.
+    fn from(array: ArrayD<T>) -> Self {
+        let dimensions = array.shape().iter().map(|&d| d as int64_t).collect::<Vec<_>>();
+        let data = if array.is_standard_layout() {
+            let length = array.len();
+            match array.into_raw_vec_and_offset() {
+                (mut data, Some(offset)) => {
+                    data.truncate(offset + length);
+                    data.drain(..offset);
+                    data
+                },
+                (data, _) => data,
+            }
+        } else {
+            array.iter().cloned().collect()
+        };
+        Tensor::from_parts(dimensions, Memory::new(data))
+    }
+}
+
+#[cfg(feature = "ndarray")]
+impl<T> From<Tensor<T>> for ArrayD<T> where T: Value {
+    /// Convert a tensor into an array.
+    ///
+    /// The data are moved without copying if the tensor is their sole owner
+    /// and they were allocated on the Rust side, which includes the decoded
+    /// strings of outputs. Otherwise, the data are copied once. In particular,
+    /// the numeric data of outputs live in memory owned by TensorFlow, which
+    /// an array cannot take over, and, therefore, such a conversion involves
+    /// exactly one copy; `view` gives access to the data without copying.
+    fn from(tensor: Tensor<T>) -> Self {
+        let (dimensions, count) = (tensor.dimensions(), tensor.count());
+        let mut data: Vec<T> = tensor.memory.into();
+        data.truncate(count);
+        debug_assert_eq!(data.len(), dimensions.iter().product::<usize>());
+        unsafe { ArrayD::from_shape_vec_unchecked(IxDyn(&dimensions), data) }
+    }
+}
+
+fn share<T>(kind: TF_DataType, dimensions: &[int64_t], memory: &Memory<T>,
+            count: usize) -> *mut TF_Tensor {
+
+    let argument = memory.share();
+    let raw = ffi!(TF_NewTensor(kind, dimensions.as_ptr(), dimensions.len() as c_int,
+                                memory.as_ptr() as *mut _,
+                                (count * mem::size_of::<T>()) as size_t,
+                                Some(memory::release::<T>), argument));
+    if raw.is_null() {
+        unsafe { memory::release::<T>(ptr::null_mut(), 0, argument) };
+    }
+    raw
+}
+
+#[cfg(test)]
+mod tests {
+    use tensor::{AnyTensor, Tensor};
+
+    #[test]
+    fn downcast() {
+        let tensor = Tensor::new(vec![1f32, 2.0, 3.0], &[3]).unwrap();
+        let tensor = AnyTensor::from_raw(tensor.to_raw().unwrap());
+        let tensor = tensor.downcast::<i32>().err().unwrap();
+        assert_eq!(tensor.dimensions(), vec![3]);
+        let tensor = tensor.downcast::<f32>().ok().unwrap();
+        assert_eq!(&tensor[..], &[1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn encode() {
+        let mut tensor = Tensor::new(vec![String::from("foo"), String::from("bar")], &[2]).unwrap();
+        let pointer = tensor.encode(2).unwrap().as_ptr();
+        assert_eq!(tensor.encode(2).unwrap().as_ptr(), pointer);
+        tensor[1].push('!');
+        assert_eq!(&tensor.encode(2).unwrap()[(2 * 8 + 4)..], b"\x04bar!");
+    }
+
+    #[test]
+    fn scalar() {
+        let tensor = Tensor::scalar(42i32);
+        assert_eq!(tensor.rank(), 0);
+        assert_eq!(tensor.count(), 1);
+        assert_eq!(tensor[[]], 42);
+        assert_eq!(tensor.rows().count(), 0);
+        assert!(Tensor::new(vec![42i32], &[]).is_ok());
+        assert!(Tensor::new(Vec::<i32>::new(), &[]).is_err());
+        assert_eq!(Tensor::new(Vec::<i32>::new(), &[3, 0]).unwrap().count(), 0);
+    }
+
+    #[cfg(feature = "half")]
+    #[test]
+    fn to_f32() {
+        use {bf16, f16};
+
+        let tensor = Tensor::new(vec![f16::from_f32(0.5), f16::from_f32(-2.0)], &[2, 1]).unwrap();
+        let tensor = tensor.to_f32();
+        assert_eq!(tensor.dimensions(), vec![2, 1]);
+        assert_eq!(&tensor[..], &[0.5, -2.0]);
+        let tensor = Tensor::new(vec![bf16::from_f32(1.5)], &[]).unwrap();
+        assert_eq!(tensor.to_f32()[[]], 1.5);
+    }
+
+    #[test]
+    fn index() {
+        let mut tensor = Tensor::new((0..24).collect::<Vec<i32>>(), &[2, 3, 4]).unwrap();
+        assert_eq!(tensor.rank(), 3);
+        assert_eq!(tensor.strides(), vec![12, 4, 1]);
+        assert_eq!(tensor[[1, 2, 3]], 23);
+        assert_eq!(tensor[[0, 1, 2]], 6);
+        assert_eq!(tensor[5], 5);
+        tensor[[1, 0, 0]] = 42;
+        assert_eq!(&tensor[11..13], &[11, 42]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_out_of_bounds() {
+        let tensor = Tensor::new((0..6).collect::<Vec<i32>>(), &[2, 3]).unwrap();
+        tensor[[0, 3]];
+    }
+
+    #[test]
+    fn new() {
+        assert!(Tensor::new(vec![1, 2, 3], &[2]).is_err());
+        assert!(Tensor::new(vec![1, 2, 3], &[4]).is_err());
+        assert!(Tensor::new(vec![1, 2, 3], &[3, 1]).is_ok());
+    }
+
+    #[test]
+    fn reshape() {
+        let mut tensor = Tensor::new((0..6).collect::<Vec<i32>>(), &[6]).unwrap();
+        assert!(tensor.reshape(&[4, 2]).is_err());
+        tensor.reshape(&[3, 2]).unwrap();
+        assert_eq!(tensor.dimensions(), vec![3, 2]);
+        assert_eq!(tensor[[2, 0]], 4);
+    }
+
+    #[test]
+    fn rows() {
+        let tensor = Tensor::new((0..6).collect::<Vec<i32>>(), &[3, 2]).unwrap();
+        let rows = tensor.rows().collect::<Vec<_>>();
+        assert_eq!(rows, vec![&[0, 1][..], &[2, 3][..], &[4, 5][..]]);
+        let tensor = Tensor::new(vec![], &[2, 0]).unwrap();
+        assert_eq!(tensor.rows().collect::<Vec<&[i32]>>(), vec![&[][..], &[][..]]);
+    }
+
+    #[test]
+    fn slice() {
+        let tensor = Tensor::new((0..6).collect::<Vec<i32>>(), &[3, 2]).unwrap();
+        let slice = tensor.slice(1..3).unwrap();
+        assert_eq!(slice.dimensions(), vec![2, 2]);
+        assert_eq!(&slice[..], &[2, 3, 4, 5]);
+        assert!(tensor.slice(2..4).is_err());
+    }
+
+    #[test]
+    fn concatenate() {
+        let a = Tensor::new(vec![1, 2], &[1, 2]).unwrap();
+        let b = Tensor::new(vec![3, 4, 5, 6], &[2, 2]).unwrap();
+        let c = Tensor::concatenate(&[a, b]).unwrap();
+        assert_eq!(c.dimensions(), vec![3, 2]);
+        assert_eq!(&c[..], &[1, 2, 3, 4, 5, 6]);
+        assert!(Tensor::concatenate(&[c, Tensor::new(vec![7], &[1, 1]).unwrap()]).is_err());
+        assert!(Tensor::<i32>::concatenate(&[]).is_err());
+    }
+
+    #[cfg(feature = "ndarray")]
+    #[test]
+    fn ndarray() {
+        use memory::Memory;
+        use ndarray::{ArrayD, IxDyn, s};
+
+        let array = ArrayD::from_shape_vec(IxDyn(&[2, 3]), vec![1, 2, 3, 4, 5, 6]).unwrap();
+        let pointer = array.as_ptr();
+        let mut tensor = Tensor::from(array);
+        assert_eq!(tensor.as_ptr(), pointer);
+        assert_eq!(tensor.dimensions(), vec![2, 3]);
+        assert_eq!(tensor.view()[[1, 0]], 4);
+        tensor.view_mut()[[0, 2]] = 42;
+        assert_eq!(&tensor[..], &[1, 2, 42, 4, 5, 6]);
+
+        let array = ArrayD::from(tensor);
+        assert_eq!(array.as_ptr(), pointer);
+
+        let data = [1, 2, 3, 4];
+        let memory = unsafe { Memory::from_raw_parts(data.as_ptr() as *mut _, data.len()) };
+        let tensor = Tensor::from_parts(vec![2, 2], memory);
+        assert_eq!(tensor.view().as_ptr(), data.as_ptr());
+        let foreign = ArrayD::from(tensor);
+        assert!(foreign.as_ptr() != data.as_ptr());
+        assert_eq!(foreign.shape(), &[2, 2]);
+        assert_eq!(foreign.as_slice().unwrap(), &data[..]);
+
+        let array = array.slice(s![.., 1..]).to_owned().into_dyn().reversed_axes();
+        let tensor = Tensor::from(array);
+        assert_eq!(tensor.dimensions(), vec![2, 2]);
+        assert_eq!(&tensor[..], &[2, 5, 42, 6]);
+    }
+}
@@ -1,9 +1,36 @@
 use ffi::TF_DataType;
 
+use Result;
+use kind::DataType;
+use string;
+
 /// A value.
-pub trait Value: 'static {
+///
+/// The trait is implemented for the element types supported by the C API of
+/// TensorFlow 0.11, which the bindings target. The API has no data types for
+/// 32- and 64-bit unsigned integers, and, therefore, `u32` and `u64` are not
+/// values.
+pub trait Value: Clone + Send + Sync + 'static {
+    /// Return the data type.
+    #[inline]
+    fn data_type() -> DataType {
+        DataType::from_raw(Self::kind())
+    }
+
     #[doc(hidden)]
     fn kind() -> TF_DataType;
+
+    #[doc(hidden)]
+    #[inline]
+    fn encode(_: &[Self]) -> Option<Vec<u8>> where Self: Sized {
+        None
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    fn decode(_: &[u8], _: usize) -> Option<Result<Vec<Self>>> where Self: Sized {
+        None
+    }
 }
 
 macro_rules! implement {
@@ -27,7 +54,11 @@ implement! {
     i64 => TF_INT64,
     u8 => TF_UINT8,
     u16 => TF_UINT16,
-    String => TF_STRING,
+    ::QInt8 => TF_QINT8,
+    ::QUInt8 => TF_QUINT8,
+    ::QInt16 => TF_QINT16,
+    ::QUInt16 => TF_QUINT16,
+    ::QInt32 => TF_QINT32,
 }
 
 #[cfg(feature = "complex")]
@@ -35,3 +66,55 @@ implement! {
     ::c32 => TF_COMPLEX64,
     ::c64 => TF_COMPLEX128,
 }
+
+#[cfg(feature = "half")]
+implement! {
+    ::bf16 => TF_BFLOAT16,
+    ::f16 => TF_HALF,
+}
+
+impl Value for String {
+    #[inline]
+    fn kind() -> TF_DataType {
+        TF_DataType::TF_STRING
+    }
+
+    #[inline]
+    fn encode(data: &[Self]) -> Option<Vec<u8>> {
+        Some(string::encode(data))
+    }
+
+    fn decode(data: &[u8], count: usize) -> Option<Result<Vec<Self>>> {
+        let elements = match string::decode(data, count) {
+            Ok(elements) => elements,
+            Err(error) => return Some(Err(error)),
+        };
+        let mut data = Vec::with_capacity(count);
+        for element in elements {
+            match String::from_utf8(element.to_vec()) {
+                Ok(element) => data.push(element),
+                _ => return Some(Err("the string data are not valid UTF-8".into())),
+            }
+        }
+        Some(Ok(data))
+    }
+}
+
+impl Value for Vec<u8> {
+    #[inline]
+    fn kind() -> TF_DataType {
+        TF_DataType::TF_STRING
+    }
+
+    #[inline]
+    fn encode(data: &[Self]) -> Option<Vec<u8>> {
+        Some(string::encode(data))
+    }
+
+    #[inline]
+    fn decode(data: &[u8], count: usize) -> Option<Result<Vec<Self>>> {
+        Some(string::decode(data, count).map(|elements| {
+            elements.into_iter().map(|element| element.to_vec()).collect()
+        }))
+    }
+}